@@ -0,0 +1,239 @@
+use clap::{Args, Parser, Subcommand};
+
+use crate::fetch_spec::{DEFAULT_JOBS, FetchOptions};
+use crate::read_wanted_spec::WantedSource;
+use crate::spec_cache::{CacheMode, DEFAULT_CACHE_DIR};
+use crate::utils::ErrorProcess;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "openapi_gen",
+    about = "Generates API clients, servers and schemas (TypeScript, Rust, Python, JSON Schema) from OpenAPI specifications selected by *.spec.json files",
+    after_help = "The old positional form is still accepted:\n    openapi_gen dir_spec dir_target base_url (prefix|all)",
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Args)]
+pub struct SpecArgs {
+    /// Directory with the *.spec.json files
+    #[arg(long)]
+    pub dir_spec: String,
+
+    /// Prepended to the url of every source of type "url"
+    #[arg(long, default_value = "")]
+    pub base_url: String,
+
+    /// Process only the given prefix, can be repeated (all prefixes by default)
+    #[arg(long = "only", value_name = "PREFIX")]
+    pub only: Vec<String>,
+
+    /// Maximum number of sources fetched at the same time
+    #[arg(long, default_value_t = DEFAULT_JOBS)]
+    pub jobs: usize,
+
+    /// Uses only the specs from the cache, recorded in openapi.lock.json
+    #[arg(long, conflicts_with = "update")]
+    pub offline: bool,
+
+    /// Locks the current version of every spec in openapi.lock.json and reports the changed specs
+    #[arg(long)]
+    pub update: bool,
+
+    /// Directory for the fetched specs, {dir_spec}/.openapi_cache by default, the files no longer locked are removed
+    #[arg(long)]
+    pub cache_dir: Option<String>,
+}
+
+impl SpecArgs {
+    /*
+        (dir_spec, options, only)
+    */
+    pub fn into_parts(self) -> (String, FetchOptions, Vec<String>) {
+        let SpecArgs { dir_spec, base_url, only, jobs, offline, update, cache_dir } = self;
+
+        let cache_mode = match (offline, update) {
+            (true, _) => CacheMode::Offline,
+            (false, true) => CacheMode::Update,
+            (false, false) => CacheMode::Fetch,
+        };
+
+        let dir_spec = normalize_dir(dir_spec);
+        let cache_dir = cache_dir.unwrap_or_else(|| format!("{dir_spec}{DEFAULT_CACHE_DIR}"));
+
+        (dir_spec, FetchOptions { base_url, jobs, cache_mode, cache_dir }, only)
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Generates the files for every selected prefix
+    Generate {
+        #[command(flatten)]
+        spec: SpecArgs,
+
+        /// Directory for the generated files
+        #[arg(long)]
+        dir_target: String,
+
+        /// Does not write anything, fails when the files in dir_target are out of date
+        #[arg(long, conflicts_with = "dry_run")]
+        check: bool,
+
+        /// Does not write anything, prints a unified diff of the changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Overwrites and deletes the generated files even if they were changed by hand
+        #[arg(long)]
+        force: bool,
+    },
+    /// The same as "generate --check"
+    Check {
+        #[command(flatten)]
+        spec: SpecArgs,
+
+        /// Directory with the generated files
+        #[arg(long)]
+        dir_target: String,
+    },
+    /// Checks the whole configuration and reports every problem, nothing is written
+    Validate {
+        #[command(flatten)]
+        spec: SpecArgs,
+    },
+    /// Creates a *.spec.json file with every operation from the source
+    Scaffold {
+        /// Directory with the *.spec.json files
+        #[arg(long)]
+        dir_spec: String,
+
+        /// Prepended to the url of the source
+        #[arg(long, default_value = "")]
+        base_url: String,
+
+        /// Name of the created file: {prefix}.spec.json
+        #[arg(long)]
+        prefix: String,
+
+        /// Source of type "url"
+        #[arg(long, conflicts_with_all = ["file", "merge"])]
+        url: Option<String>,
+
+        /// Source of type "file", relative to dir_spec
+        #[arg(long, conflicts_with = "merge")]
+        file: Option<String>,
+
+        /// Adds the new operations to the existing file, the source is taken from the file
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Generates the files and regenerates the affected prefixes whenever the configuration or a local spec file changes
+    Watch {
+        #[command(flatten)]
+        spec: SpecArgs,
+
+        /// Directory for the generated files
+        #[arg(long)]
+        dir_target: String,
+
+        /// Overwrites and deletes the generated files even if they were changed by hand
+        #[arg(long)]
+        force: bool,
+    },
+    /// Prints the parsed and rewritten specification of the selected methods as json
+    DumpIr {
+        #[command(flatten)]
+        spec: SpecArgs,
+    },
+}
+
+pub fn normalize_dir(dir: String) -> String {
+    if dir.is_empty() || dir.ends_with('/') {
+        dir
+    } else {
+        format!("{dir}/")
+    }
+}
+
+#[test]
+fn test_normalize_dir() {
+    assert_eq!(normalize_dir("spec".into()), "spec/");
+    assert_eq!(normalize_dir("spec/".into()), "spec/");
+}
+
+pub fn get_scaffold_source(url: Option<String>, file: Option<String>, merge: bool) -> Result<Option<WantedSource>, ErrorProcess> {
+    match (url, file, merge) {
+        (Some(url), None, false) => Ok(Some(WantedSource::Url { url, fix_url_param: None, rewrite: None, http: None })),
+        (None, Some(file), false) => Ok(Some(WantedSource::File { file, fix_url_param: None, rewrite: None })),
+        (None, None, true) => Ok(None),
+        _ => Err(ErrorProcess::message("scaffold: expected --url, --file or --merge")),
+    }
+}
+
+const SUBCOMMANDS: &[&str] = &["generate", "check", "validate", "scaffold", "watch", "dump-ir", "help"];
+
+/*
+    openapi_gen dir_spec dir_target base_url (prefix|all)
+    ->
+    openapi_gen generate --dir-spec dir_spec --dir-target dir_target --base-url base_url [--only prefix]
+*/
+fn parse_legacy(args: &[String]) -> Option<Command> {
+    match args {
+        [_, dir_spec, dir_target, base_url, target_spec] => {
+            if SUBCOMMANDS.contains(&dir_spec.as_str()) || dir_spec.starts_with('-') {
+                return None;
+            }
+
+            let only = if target_spec == "all" {
+                Vec::new()
+            } else {
+                vec!(target_spec.clone())
+            };
+
+            Some(Command::Generate {
+                spec: SpecArgs {
+                    dir_spec: dir_spec.clone(),
+                    base_url: base_url.clone(),
+                    only,
+                    jobs: DEFAULT_JOBS,
+                    offline: false,
+                    update: false,
+                    cache_dir: None,
+                },
+                dir_target: dir_target.clone(),
+                check: false,
+                dry_run: false,
+                force: false,
+            })
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parse_legacy() {
+    let args = ["openapi_gen", "spec/", "out", "http://api", "all"].map(String::from);
+
+    match parse_legacy(&args) {
+        Some(Command::Generate { spec, dir_target, .. }) => {
+            assert_eq!(spec.dir_spec, "spec/");
+            assert_eq!(spec.only, Vec::<String>::new());
+            assert_eq!(dir_target, "out");
+        },
+        command => panic!("unexpected {command:?}"),
+    }
+
+    let args = ["openapi_gen", "generate", "--dir-spec", "spec", "--dir-target"].map(String::from);
+    assert!(parse_legacy(&args).is_none());
+}
+
+pub fn parse_command(args: Vec<String>) -> Command {
+    if let Some(command) = parse_legacy(&args) {
+        return command;
+    }
+
+    Cli::parse_from(args).command
+}
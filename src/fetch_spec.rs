@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::open_api_spec::SpecOpenApi;
+use crate::parse_spec::parse_spec;
+use crate::manifest::content_hash;
+use crate::read_wanted_spec::{HttpConfig, RetryConfig, WantedSource};
+use crate::spec_cache::{CacheMode, DEFAULT_CACHE_DIR, LOCK_FILE, SpecLock, prune_cache, read_cache, write_cache};
+use crate::utils::ErrorProcess;
+
+pub const DEFAULT_JOBS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    pub base_url: String,                   //prepended to the url of every source of type "url"
+    pub jobs: usize,                        //maximum number of sources fetched at the same time
+    pub cache_mode: CacheMode,
+    pub cache_dir: String,                  //the fetched sources of type "url", {dir_spec}.openapi_cache by default
+}
+
+impl FetchOptions {
+    pub fn new(dir_spec: &String, base_url: String) -> FetchOptions {
+        FetchOptions {
+            base_url,
+            jobs: DEFAULT_JOBS,
+            cache_mode: CacheMode::Fetch,
+            cache_dir: format!("{dir_spec}{DEFAULT_CACHE_DIR}"),
+        }
+    }
+}
+
+/*
+    The path of the file or the full url
+*/
+fn source_location(dir_spec: &String, options: &FetchOptions, source: &WantedSource) -> String {
+    match source {
+        WantedSource::File { file, .. } => format!("{dir_spec}{file}"),
+        WantedSource::Url { url, .. } => {
+            let base_url = &options.base_url;
+            format!("{base_url}{url}")
+        },
+    }
+}
+
+/*
+    Prefixes with the same key share one fetched and parsed specification and one entry of the lock.
+    The same url fetched with another http config is another source:
+    http://api/open-api {"headers":{"Authorization":"Bearer ${SPEC_TOKEN}"}}
+*/
+pub fn source_key(dir_spec: &String, options: &FetchOptions, source: &WantedSource) -> String {
+    let location = source_location(dir_spec, options, source);
+
+    let http = match source {
+        WantedSource::Url { http: Some(http), .. } => serde_json::to_string(http).unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    if http.is_empty() || http == "{}" {
+        return location;
+    }
+
+    format!("{location} {http}")
+}
+
+#[test]
+fn test_source_key() {
+    let options = FetchOptions::new(&"spec/".to_string(), "http://api".into());
+    let source = |http: Option<&str>| WantedSource::Url {
+        url: "/open-api".into(),
+        fix_url_param: None,
+        rewrite: None,
+        http: http.map(|http| serde_json::from_str(http).unwrap()),
+    };
+
+    let dir_spec = "spec/".to_string();
+    let plain = source_key(&dir_spec, &options, &source(None));
+    let token_a = source_key(&dir_spec, &options, &source(Some(r#"{"headers": {"Authorization": "a"}}"#)));
+    let token_b = source_key(&dir_spec, &options, &source(Some(r#"{"headers": {"Authorization": "b"}}"#)));
+
+    assert_eq!(plain, "http://api/open-api");
+    assert_eq!(source_key(&dir_spec, &options, &source(Some("{}"))), plain);
+    assert_eq!(token_a, r#"http://api/open-api {"headers":{"Authorization":"a"}}"#);
+    assert_ne!(token_a, token_b);
+}
+
+async fn fetch_source(dir_spec: &String, options: &FetchOptions, source: &WantedSource) -> Result<String, ErrorProcess> {
+    match source {
+        WantedSource::File { .. } => {
+            let file_full = source_location(dir_spec, options, source);
+
+            log::info!("read file with spec: {file_full}");
+
+            let content = tokio::fs::read_to_string(&file_full).await.map_err({
+                let file_full = file_full.clone();
+                move |err| {
+                    ErrorProcess::message(format!("error read content {file_full} -> {err}"))
+                }
+            })?;
+
+            Ok(content)
+        },
+        WantedSource::Url { http, .. } => {
+            let url_full = source_location(dir_spec, options, source);
+
+            log::info!("read url with spec: {url_full}");
+
+            let http = http.clone().unwrap_or_default();
+            fetch_url(dir_spec, &url_full, &http).await
+        }
+    }
+}
+
+/*
+    "Bearer ${SPEC_TOKEN}" -> "Bearer abc"
+*/
+fn interpolate_env(value: &str, get_env: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+
+        let after = &rest[start + 2..];
+        let end = match after.find('}') {
+            Some(end) => end,
+            None => {
+                return Err(format!("unclosed ${{ in {value}"));
+            }
+        };
+
+        let name = &after[..end];
+        match get_env(name) {
+            Some(env_value) => out.push_str(&env_value),
+            None => {
+                return Err(format!("the environment variable {name} is not set"));
+            }
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[test]
+fn test_interpolate_env() {
+    let get_env = |name: &str| if name == "TOKEN" { Some("abc".to_string()) } else { None };
+
+    assert_eq!(interpolate_env("Bearer ${TOKEN}", get_env), Ok("Bearer abc".to_string()));
+    assert_eq!(interpolate_env("plain", get_env), Ok("plain".to_string()));
+    assert!(interpolate_env("${MISSING}", get_env).is_err());
+    assert!(interpolate_env("${TOKEN", get_env).is_err());
+}
+
+fn build_client(dir_spec: &String, url_full: &String, http: &HttpConfig) -> Result<reqwest::Client, ErrorProcess> {
+    let mut headers = HeaderMap::new();
+
+    for (name, value) in http.headers.iter() {
+        let value = interpolate_env(value, |name| std::env::var(name).ok()).map_err(|err| {
+            ErrorProcess::message(format!("{url_full}: header {name}: {err}"))
+        })?;
+
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|err| {
+            ErrorProcess::message(format!("{url_full}: header {name}: {err}"))
+        })?;
+
+        let header_value = HeaderValue::from_str(&value).map_err(|err| {
+            ErrorProcess::message(format!("{url_full}: header {name}: {err}"))
+        })?;
+
+        headers.insert(header_name, header_value);
+    }
+
+    let mut builder = reqwest::Client::builder().default_headers(headers);
+
+    if let Some(timeout) = http.timeout {
+        builder = builder.timeout(Duration::from_secs(timeout));
+    }
+
+    if let Some(ca_cert) = &http.ca_cert {
+        let path = format!("{dir_spec}{ca_cert}");
+
+        let pem = std::fs::read(&path).map_err(|err| {
+            ErrorProcess::message(format!("error read content {path} -> {err}"))
+        })?;
+
+        let certificate = reqwest::Certificate::from_pem(&pem).map_err(|err| {
+            ErrorProcess::message(format!("{url_full}: ca_cert {path} -> {err}"))
+        })?;
+
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    if let Some(proxy) = &http.proxy {
+        let proxy = reqwest::Proxy::all(proxy).map_err(|err| {
+            ErrorProcess::message(format!("{url_full}: proxy {proxy} -> {err}"))
+        })?;
+
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().map_err(|err| {
+        ErrorProcess::message(format!("{url_full}: error create http client -> {err}"))
+    })
+}
+
+enum FetchError {
+    Retry(String),                          //network error, timeout, 5xx or 429
+    Fail(String),
+}
+
+async fn fetch_url_once(client: &reqwest::Client, url_full: &String) -> Result<String, FetchError> {
+    let response = client.get(url_full).send().await.map_err(|err| {
+        FetchError::Retry(format!("error fetch {url_full} -> {err}"))
+    })?;
+
+    let status = response.status();
+
+    if !status.is_success() {
+        let message = format!("error fetch {url_full} -> unexpected status {status}");
+
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(FetchError::Retry(message));
+        }
+
+        return Err(FetchError::Fail(message));
+    }
+
+    response.text().await.map_err(|err| {
+        FetchError::Retry(format!("error fetch(text) {url_full} -> {err}"))
+    })
+}
+
+async fn fetch_url(dir_spec: &String, url_full: &String, http: &HttpConfig) -> Result<String, ErrorProcess> {
+    let client = build_client(dir_spec, url_full, http)?;
+
+    let (attempts, mut backoff_ms) = match &http.retry {
+        Some(RetryConfig { attempts, backoff_ms }) => (*attempts, *backoff_ms),
+        None => (0, 0),
+    };
+
+    let mut attempt = 0;
+
+    loop {
+        match fetch_url_once(&client, url_full).await {
+            Ok(content) => {
+                return Ok(content);
+            },
+            Err(FetchError::Retry(message)) if attempt < attempts => {
+                attempt += 1;
+                log::warn!("{message}, retry {attempt}/{attempts} in {backoff_ms} ms");
+
+                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                backoff_ms *= 2;
+            },
+            Err(FetchError::Retry(message)) | Err(FetchError::Fail(message)) => {
+                return Err(ErrorProcess::message(message));
+            }
+        }
+    }
+}
+
+pub async fn fetch_spec(dir_spec: &String, options: &FetchOptions, source: &WantedSource) -> Result<SpecOpenApi, ErrorProcess> {
+    let content = fetch_source(dir_spec, options, source).await?;
+    parse_spec(content)
+}
+
+async fn read_locked(options: &FetchOptions, key: &String, locked: &Option<String>) -> Result<String, ErrorProcess> {
+    let cache_dir = &options.cache_dir;
+
+    let hash = match locked {
+        Some(hash) => hash,
+        None => {
+            return Err(ErrorProcess::message(format!("{key} is missing in {LOCK_FILE}")));
+        }
+    };
+
+    match read_cache(cache_dir, hash).await? {
+        Some(content) => {
+            log::info!("read cached spec: {key}");
+            Ok(content)
+        },
+        None => Err(ErrorProcess::message(format!("{key} is missing in the cache {cache_dir}"))),
+    }
+}
+
+/*
+    Sources of type "url" go through the cache, the returned hash is recorded in the lock.
+    The fetched content is cached when it is going to be locked: always with CacheMode::Update, otherwise
+    only for a source missing in the lock or matching it. The cache is read only offline or when the fetch has failed.
+*/
+async fn fetch_spec_locked(
+    dir_spec: &String,
+    options: &FetchOptions,
+    source: &WantedSource,
+    locked: Option<String>,
+) -> Result<(SpecOpenApi, Option<String>), ErrorProcess> {
+    if let WantedSource::File { .. } = source {
+        return Ok((fetch_spec(dir_spec, options, source).await?, None));
+    }
+
+    let key = source_key(dir_spec, options, source);
+
+    let content = if options.cache_mode == CacheMode::Offline {
+        read_locked(options, &key, &locked).await.map_err(|err| {
+            ErrorProcess::message(format!("offline: {err}"))
+        })?
+    } else {
+        match (options.cache_mode, fetch_source(dir_spec, options, source).await) {
+            (cache_mode, Ok(content)) => {
+                let hash = content_hash(&content);
+
+                if cache_mode == CacheMode::Update || locked.is_none() || locked.as_ref() == Some(&hash) {
+                    write_cache(&options.cache_dir, &hash, &content).await?;
+                }
+
+                content
+            },
+            (CacheMode::Fetch, Err(err)) => {
+                let content = read_locked(options, &key, &locked).await.map_err(|cache_err| {
+                    ErrorProcess::message(format!("{err}, no fallback: {cache_err}"))
+                })?;
+
+                log::warn!("{err}, using the locked version from the cache");
+                content
+            },
+            (_, Err(err)) => {
+                return Err(err);
+            },
+        }
+    };
+
+    let hash = content_hash(&content);
+    Ok((parse_spec(content)?, Some(hash)))
+}
+
+/*
+    Every distinct source is fetched and parsed once, at most `options.jobs` at the same time.
+    The sources missing in the lock are added to it, the locked hashes are replaced only with CacheMode::Update
+    and then the changes are reported on stderr. The cached files no longer locked are removed with the lock write.
+    source_key -> the specification or the error message
+*/
+pub async fn fetch_specs(
+    dir_spec: &String,
+    options: &FetchOptions,
+    sources: Vec<WantedSource>,
+) -> Result<HashMap<String, Result<SpecOpenApi, String>>, ErrorProcess> {
+    let mut lock = SpecLock::read(dir_spec).await?;
+
+    let semaphore = Arc::new(Semaphore::new(options.jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut keys = Vec::new();
+
+    for source in sources {
+        let key = source_key(dir_spec, options, &source);
+
+        if keys.contains(&key) {
+            continue;
+        }
+
+        keys.push(key.clone());
+
+        let locked = lock.specs.get(&key).cloned();
+        let semaphore = semaphore.clone();
+        let dir_spec = dir_spec.clone();
+        let options = options.clone();
+
+        tasks.spawn(async move {
+            let result = match semaphore.acquire_owned().await {
+                Ok(_permit) => fetch_spec_locked(&dir_spec, &options, &source, locked).await.map_err(|err| err.to_string()),
+                Err(err) => Err(err.to_string()),
+            };
+
+            (key, result)
+        });
+    }
+
+    let mut out = HashMap::new();
+    let mut changed = Vec::new();
+
+    while let Some(result) = tasks.join_next().await {
+        let (key, result) = result.map_err(|err| {
+            ErrorProcess::message(format!("fetch: the task has failed -> {err}"))
+        })?;
+
+        let result = match result {
+            Ok((spec, Some(hash))) => {
+                match lock.specs.get(&key) {
+                    Some(old) if *old == hash => {},
+                    Some(_) if options.cache_mode != CacheMode::Update => {
+                        log::info!("{key} has changed since the last lock, run with --update to lock the new version");
+                    },
+                    old => {
+                        changed.push((key.clone(), old.is_none()));
+                        lock.specs.insert(key.clone(), hash);
+                    },
+                }
+
+                Ok(spec)
+            },
+            Ok((spec, None)) => Ok(spec),
+            Err(err) => Err(err),
+        };
+
+        out.insert(key, result);
+    }
+
+    if !changed.is_empty() {
+        lock.write(dir_spec).await?;
+        prune_cache(&options.cache_dir, &lock).await?;
+    }
+
+    if options.cache_mode == CacheMode::Update {
+        changed.sort();
+
+        for (key, added) in changed.iter() {
+            if *added {
+                eprintln!("added: {key}");
+            } else {
+                eprintln!("changed: {key}");
+            }
+        }
+
+        let count = changed.len();
+        eprintln!("Specs changed since the last lock: {count}");
+    }
+
+    Ok(out)
+}
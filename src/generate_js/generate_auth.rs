@@ -0,0 +1,136 @@
+use crate::open_api_spec::{ApiKeyIn, SecuritySchemeType, SpecHandlerType};
+use super::fix_to_camel_case::fix_to_camel_case;
+use super::generate_ident::generate_ident;
+use super::{to_big_camel_case, word_first_letter_to_lowercase};
+
+pub struct AuthJs {
+    pub args_decl: String,              //", backendToken: string"
+    pub args_call: String,              //", backendToken"
+    pub fetch_params: String,           //lines added to paramsFetch
+    pub query: Vec<String>,             //"'api_key': apiKey"
+}
+
+fn get_arg_name(scheme_name: &str) -> String {
+    let name = fix_to_camel_case(&scheme_name.to_string());
+    word_first_letter_to_lowercase(&to_big_camel_case(&name))
+}
+
+#[test]
+fn test_get_arg_name() {
+    assert_eq!(get_arg_name("partnerKey"), "partnerKey");
+    assert_eq!(get_arg_name("api_key"), "apiKey");
+    assert_eq!(get_arg_name("X-Api-Key"), "xApiKey");
+}
+
+/*
+    With the optional authentication the arguments accept undefined, a header is sent only when its arguments are given
+*/
+pub fn generate_auth(spec: &SpecHandlerType) -> AuthJs {
+    let left = '{';
+    let right = '}';
+
+    let optional = spec.security_optional;
+    let or_undefined = if optional { " | undefined" } else { "" };
+
+    let mut args_decl = Vec::<String>::new();
+    let mut args_call = Vec::<String>::new();
+    let mut fetch_params = Vec::<String>::new();
+    let mut headers = Vec::<(String, Vec<String>)>::new();         //('Authorization': ..., the arguments used)
+    let mut cookies = Vec::<(String, String)>::new();
+    let mut query = Vec::<String>::new();
+
+    for security in spec.security.iter() {
+        match &security.scheme {
+            SecuritySchemeType::Bearer => {
+                //the token is passed to fetchGeneralRaw, which adds the Authorization header
+                if args_call.iter().any(|arg| arg == "backendToken") {
+                    continue;
+                }
+
+                args_decl.push(format!("backendToken: string{or_undefined}"));
+                args_call.push("backendToken".into());
+                fetch_params.push("backendToken,".into());
+            },
+            SecuritySchemeType::Basic => {
+                let arg = get_arg_name(&security.name);
+                args_decl.push(format!("{arg}: {left} username: string, password: string {right}{or_undefined}"));
+                args_call.push(arg.clone());
+                headers.push((
+                    format!("'Authorization': `Basic ${left}btoa(`${left}{arg}.username{right}:${left}{arg}.password{right}`){right}`"),
+                    vec!(arg),
+                ));
+            },
+            SecuritySchemeType::ApiKey { where_in, name } => {
+                let arg = get_arg_name(&security.name);
+                args_decl.push(format!("{arg}: string{or_undefined}"));
+                args_call.push(arg.clone());
+
+                match where_in {
+                    ApiKeyIn::Header => headers.push((format!("'{name}': {arg}"), vec!(arg))),
+                    ApiKeyIn::Query => query.push(format!("'{name}': {arg}")),
+                    ApiKeyIn::Cookie => cookies.push((name.clone(), arg)),
+                }
+            },
+        }
+    }
+
+    if !cookies.is_empty() {
+        let value = cookies.iter().map(|(name, arg)| format!("{name}=${left}{arg}{right}")).collect::<Vec<String>>().join("; ");
+        let args = cookies.into_iter().map(|(_, arg)| arg).collect();
+        headers.push((format!("'Cookie': `{value}`"), args));
+    }
+
+    let headers = headers
+        .into_iter()
+        .map(|(header, args)| match optional {
+            true => {
+                let condition = args.iter().map(|arg| format!("{arg} === undefined")).collect::<Vec<String>>().join(" || ");
+                format!("...({condition} ? {left}{right} : {left} {header} {right}),")
+            },
+            false => format!("{header},"),
+        })
+        .collect::<Vec<String>>();
+
+    if !headers.is_empty() {
+        let ident_headers = generate_ident(12);
+        let ident_end = generate_ident(8);
+        let headers = headers
+            .into_iter()
+            .map(|header| format!("{ident_headers}{header}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        fetch_params.push(format!("headers: {left}\n{headers}\n{ident_end}{right},"));
+    }
+
+    let ident = generate_ident(8);
+
+    AuthJs {
+        args_decl: args_decl.into_iter().map(|arg| format!(", {arg}")).collect(),
+        args_call: args_call.into_iter().map(|arg| format!(", {arg}")).collect(),
+        fetch_params: fetch_params.into_iter().map(|line| format!("{ident}{line}\n")).collect(),
+        query,
+    }
+}
+
+#[test]
+fn test_generate_auth_optional() {
+    use crate::open_api_spec::SecurityType;
+
+    let mut spec = SpecHandlerType::new();
+    spec.security_optional = true;
+    spec.security.push(SecurityType {
+        name: "partnerKey".into(),
+        scheme: SecuritySchemeType::ApiKey { where_in: ApiKeyIn::Header, name: "X-Partner-Key".into() },
+    });
+
+    let auth = generate_auth(&spec);
+
+    assert_eq!(auth.args_decl, ", partnerKey: string | undefined");
+    assert_eq!(auth.fetch_params, [
+        "        headers: {",
+        "            ...(partnerKey === undefined ? {} : { 'X-Partner-Key': partnerKey }),",
+        "        },",
+        "",
+    ].join("\n"));
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::open_api_spec::{OperationRef, SpecOpenApi};
+use crate::utils::ErrorProcess;
+use super::{to_big_camel_case, word_first_letter_to_lowercase};
+
+/*
+    export { openapi_lottery_getDraw, openapiLotteryGetDrawRequest } from './openapi_lottery_getDraw';
+    export type { OpenapiLotteryGetDrawParamsType, ... } from './openapi_lottery_getDraw';
+
+    export const openapiLotteryOperations = {
+        getDraw: { method: 'GET', path: '/lottery/draws/{universe}' },
+    } as const;
+*/
+pub fn generate_index(prefix: &String, spec: &SpecOpenApi, methods: &HashMap<String, OperationRef>) -> Result<String, ErrorProcess> {
+    let mut methods = methods.iter().collect::<Vec<_>>();
+    methods.sort_by_key(|(method_name, _)| *method_name);
+
+    let mut exports = Vec::<String>::new();
+    let mut registry = Vec::<String>::new();
+
+    for (method_name, OperationRef { url, method }) in methods {
+        let name_in_file = format!("openapi_{prefix}_{method_name}");
+        let name_in_file_camelcase_big = to_big_camel_case(&name_in_file);
+        let name_in_file_camelcase_small = word_first_letter_to_lowercase(&name_in_file_camelcase_big);
+
+        let handler = spec.get_handler(url, method)?;
+
+        let mut types = vec!(
+            format!("{name_in_file_camelcase_big}ParamsType"),
+            format!("{name_in_file_camelcase_big}ResponseType"),
+        );
+
+        if handler.responses.get_sorted().iter().any(|(code, _)| **code == 200) {
+            types.push(format!("{name_in_file_camelcase_big}Response200Type"));
+        }
+
+        let types = types.join(", ");
+        exports.push(format!("export {{ {name_in_file}, {name_in_file_camelcase_small}Request }} from './{name_in_file}';"));
+        exports.push(format!("export type {{ {types} }} from './{name_in_file}';"));
+
+        let method = method.to_upper_case();
+        registry.push(format!("    {method_name}: {{ method: '{method}', path: '{url}' }},"));
+    }
+
+    let registry_name = word_first_letter_to_lowercase(&to_big_camel_case(&format!("openapi_{prefix}_operations")));
+    let exports = exports.join("\n");
+    let registry = registry.join("\n");
+
+    Ok(format!(r#"//The contents of this file have been generated automatically. Do not edit this file.
+
+{exports}
+
+export const {registry_name} = {{
+{registry}
+}} as const;
+"#))
+}
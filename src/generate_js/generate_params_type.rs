@@ -9,7 +9,7 @@ fn add_require(require: bool, type_param: impl Into<String>) -> String {
     }
 }
 
-fn generate_object_prop_name(key: &String) -> String {
+pub fn generate_object_prop_name(key: &String) -> String {
     let first = key.chars().next();
 
     match first {
@@ -0,0 +1,113 @@
+use crate::generate_js::generate_ident::generate_ident;
+use crate::open_api_type::OpenApiType;
+use crate::open_api_spec::{OpenApiMethod, SpecHandlerType};
+use super::generate_params_type::generate_object_prop_name;
+
+fn add_require(require: bool, type_param: impl Into<String>) -> String {
+    let type_param = type_param.into();
+    match require {
+        true => type_param,
+        false => format!("{type_param}.nullable().optional()")
+    }
+}
+
+fn generate_type_zod(ident: u32, type_param: &OpenApiType) -> String {
+    let left = '{';
+    let right = '}';
+
+    match type_param {
+        OpenApiType::LiteralString { required, value } => add_require(*required, format!("z.literal('{value}')")),
+        OpenApiType::String { required } => add_require(*required, "z.string()"),
+        OpenApiType::Number { required } => add_require(*required, "z.number()"),
+        OpenApiType::Boolean { required } => add_require(*required, "z.boolean()"),
+        OpenApiType::Unknown => "z.unknown()".into(),
+        OpenApiType::Array { required, items } => {
+            let items = generate_type_zod(ident, items);
+            let result = format!("z.array({items})");
+            add_require(*required, result)
+        },
+        OpenApiType::Object { required, props } => {
+            let next_ident = ident + 4;
+            let mut out = Vec::<String>::new();
+
+            out.push(format!("z.object({left}"));
+
+            for (key, value) in props.get_sorted() {
+                let ident_str = generate_ident(next_ident);
+                let value_std = generate_type_zod(next_ident, value);
+                let key = generate_object_prop_name(key);
+                out.push(format!("{ident_str}{key}: {value_std},"));
+            }
+
+            let end_iden = generate_ident(ident);
+            out.push(format!("{end_iden}{right})"));
+
+            add_require(*required, out.join("\n"))
+        },
+        OpenApiType::Union { required, list } => {
+            let mut result_types = Vec::<String>::new();
+
+            for list_item in list {
+                result_types.push(generate_type_zod(0, list_item));
+            }
+
+            //z.union requires at least two options
+            let union_type = match result_types.len() {
+                0 => "z.never()".to_string(),
+                1 => result_types.remove(0),
+                _ => {
+                    let result_str = result_types.join(", ");
+                    format!("z.union([{result_str}])")
+                }
+            };
+
+            add_require(*required, union_type)
+        }
+        OpenApiType::Record { required, value } => {
+            let inner_type = generate_type_zod(ident, value);
+            let value_srt = format!("z.record(z.string(), {inner_type})");
+            add_require(*required, value_srt)
+        }
+    }
+}
+
+#[test]
+fn test_generate_type_zod() {
+    let type_param = OpenApiType::Union {
+        required: false,
+        list: vec!(
+            OpenApiType::LiteralString { required: true, value: "pl".into() },
+            OpenApiType::Number { required: true },
+        ),
+    };
+
+    assert_eq!(generate_type_zod(0, &type_param), "z.union([z.literal('pl'), z.number()]).nullable().optional()");
+}
+
+pub fn generate_response_zod(spec: &SpecHandlerType, url: &String, method: &OpenApiMethod) -> String {
+    let left = '{';
+    let right = '}';
+    let mut out = Vec::<String>::new();
+
+    for (code, response) in spec.responses.get_sorted() {
+        let type_zod = generate_type_zod(0, response);
+
+        out.push(format!("const Response{code}Schema = {type_zod};"));
+        out.push("".into());
+        out.push(format!("export type Response{code}Type = z.infer<typeof Response{code}Schema>;"));
+        out.push("".into());
+        out.push(format!("export const decodeResponse{code} = (data: unknown): Response{code}Type => {left}"));
+        out.push(format!("    const decodeResult = Response{code}Schema.safeParse(data);"));
+        out.push(format!("    if (decodeResult.success) {left}"));
+        out.push("        return decodeResult.data;".into());
+        out.push(format!("    {right}"));
+        out.push(format!("    throw Error('Response decoding error {url} -> {method} -> {code}');"));
+        out.push(format!("{right};"));
+        out.push("".into());
+        out.push("".into());
+        out.push("".into());
+        out.push("".into());
+    }
+
+    out.join("\n")
+}
@@ -0,0 +1,67 @@
+/*
+    The helpers used by the generated files, based only on the native fetch (no project-specific imports).
+    They have the same signatures as fetchGeneralRaw and jsonParse from src_common.
+*/
+pub fn generate_runtime() -> String {
+    r#"//The contents of this file have been generated automatically. Do not edit this file.
+
+export interface FetchGeneralRawResponseType {
+    status: number,
+    body: string,
+}
+
+export interface FetchGeneralRawParamsType {
+    url: string,
+    body: unknown,
+    backendToken?: string,
+    headers?: Record<string, string>,
+    timeout: number,
+}
+
+export const fetchGeneralRaw = async (method: string, params: FetchGeneralRawParamsType): Promise<FetchGeneralRawResponseType> => {
+    const headers: Record<string, string> = { ...params.headers };
+
+    if (params.backendToken !== undefined) {
+        headers['Authorization'] = `Bearer ${params.backendToken}`;
+    }
+
+    if (params.body !== undefined) {
+        headers['Content-Type'] = 'application/json';
+    }
+
+    const response = await fetch(params.url, {
+        method,
+        headers,
+        body: params.body === undefined ? undefined : JSON.stringify(params.body),
+        signal: AbortSignal.timeout(params.timeout),
+    });
+
+    return {
+        status: response.status,
+        body: await response.text(),
+    };
+};
+
+export type JsonParseResultType = {
+    type: 'json',
+    json: unknown,
+} | {
+    type: 'text',
+    text: string,
+};
+
+export const jsonParse = (text: string): JsonParseResultType => {
+    try {
+        return {
+            type: 'json',
+            json: JSON.parse(text),
+        };
+    } catch (_error) {
+        return {
+            type: 'text',
+            text,
+        };
+    }
+};
+"#.into()
+}
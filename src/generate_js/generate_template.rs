@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use minijinja::Environment;
+use serde::Serialize;
+
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{OpenApiMethod, OperationRef, ParametersType, SpecHandlerType};
+use crate::open_api_type::OpenApiType;
+use crate::output::RenderedFile;
+use crate::read_wanted_spec::TemplateOptions;
+use crate::utils::ErrorProcess;
+use super::fix_to_camel_case::fix_to_camel_case;
+use super::generate_params_type::{generate_params_type, generate_type_ts};
+use super::{
+    add_import_query_string, generate_auth, generate_codecs, generate_generic_response, generate_url, get_body,
+    get_params_name, to_big_camel_case, word_first_letter_to_lowercase,
+};
+
+/*
+    /lottery/{universe} ->
+    [{ "text": "lottery", "param": null }, { "text": "{universe}", "param": "universe" }]
+*/
+#[derive(Serialize)]
+struct UrlSegment {
+    text: String,
+    param: Option<String>,                  //the name of the field in ParamsType
+}
+
+#[derive(Serialize)]
+struct ParamContext<'a> {
+    #[serde(flatten)]
+    param: &'a ParametersType,              //where_in, name, api_type
+    field: String,                          //the name of the field in ParamsType
+    type_ts: String,
+}
+
+#[derive(Serialize)]
+struct ResponseContext<'a> {
+    code: u16,
+    api_type: &'a OpenApiType,
+    type_ts: String,
+}
+
+#[derive(Serialize)]
+struct AuthContext {
+    args_decl: String,                      //", backendToken: string"
+    args_call: String,                      //", backendToken"
+    fetch_params: String,                   //lines for paramsFetch
+}
+
+/*
+    The context of the template, the "rendered" fields contain ready TS code:
+    {{ rendered.params_type }}, {{ rendered.codecs }}, `${api_url}{{ rendered.url }}`
+*/
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    prefix: &'a String,
+    method_name: &'a String,
+    name_in_file: String,                   //openapi_lottery_getDraw
+    name_camel_case: String,                //OpenapiLotteryGetDraw
+    name_camel_case_small: String,          //openapiLotteryGetDraw
+    operation_id: &'a Option<String>,
+    tags: &'a Vec<String>,
+    method: &'a OpenApiMethod,              //get
+    method_upper: String,                   //GET
+    url: &'a String,                        //the path from the specification
+    url_segments: Vec<UrlSegment>,
+    params: Vec<ParamContext<'a>>,
+    responses: Vec<ResponseContext<'a>>,
+    auth: AuthContext,
+    rendered: RenderedContext,
+}
+
+#[derive(Serialize)]
+struct RenderedContext {
+    import_codec: String,                   //the imports of io-ts or zod
+    import_query_string: String,
+    params_type: String,                    //export interface ParamsType
+    params_name: String,                    //"params" or "_params" when there are no parameters
+    codecs: String,                         //Response{code}Type and decodeResponse{code}
+    url: String,                            //the url after api_url, with the parameters and the query
+    body: String,                           //"params.requestBody" or "undefined"
+    response_union: String,                 //{ status: 200, body: Response200Type } | ...
+    response_ifs: String,                   //the decoding of the response by the status
+}
+
+fn get_url_segments(url: &str) -> Vec<UrlSegment> {
+    url.split('/')
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| {
+            let param = if chunk.starts_with('{') && chunk.ends_with('}') {
+                Some(fix_to_camel_case(&chunk[1..chunk.len() - 1].to_string()))
+            } else {
+                None
+            };
+
+            UrlSegment { text: chunk.to_string(), param }
+        })
+        .collect()
+}
+
+#[test]
+fn test_get_url_segments() {
+    let segments = get_url_segments("/lottery/{universe-id}");
+
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].param, None);
+    assert_eq!(segments[1].text, "{universe-id}");
+    assert_eq!(segments[1].param, Some("universeId".to_string()));
+}
+
+fn get_context<'a>(
+    prefix: &'a String,
+    method_name: &'a String,
+    url: &'a String,
+    method: &'a OpenApiMethod,
+    handler: &'a SpecHandlerType,
+    options: &TemplateOptions,
+) -> TemplateContext<'a> {
+    let name_in_file = format!("openapi_{prefix}_{method_name}");
+    let name_camel_case = to_big_camel_case(&name_in_file);
+    let name_camel_case_small = word_first_letter_to_lowercase(&name_camel_case);
+
+    let params = handler.parameters
+        .iter()
+        .map(|param| ParamContext {
+            param,
+            field: fix_to_camel_case(&param.name),
+            type_ts: generate_type_ts(0, &param.api_type),
+        })
+        .collect();
+
+    let responses = handler.responses
+        .get_sorted()
+        .into_iter()
+        .map(|(code, api_type)| ResponseContext {
+            code: *code,
+            api_type,
+            type_ts: generate_type_ts(0, api_type),
+        })
+        .collect();
+
+    let auth = generate_auth::generate_auth(handler);
+    let (import_codec, codecs) = generate_codecs(handler, url, method, options.codec);
+    let (response_union, response_ifs) = generate_generic_response(&handler.responses);
+
+    let rendered = RenderedContext {
+        import_codec,
+        import_query_string: add_import_query_string(handler).to_string(),
+        params_type: generate_params_type(handler),
+        params_name: get_params_name(handler),
+        codecs,
+        url: generate_url(url.clone(), handler, &auth.query),
+        body: get_body(handler),
+        response_union,
+        response_ifs,
+    };
+
+    TemplateContext {
+        prefix,
+        method_name,
+        name_in_file,
+        name_camel_case,
+        name_camel_case_small,
+        operation_id: &handler.operation_id,
+        tags: &handler.tags,
+        method,
+        method_upper: method.to_upper_case(),
+        url,
+        url_segments: get_url_segments(url),
+        params,
+        responses,
+        auth: AuthContext {
+            args_decl: auth.args_decl,
+            args_call: auth.args_call,
+            fetch_params: auth.fetch_params,
+        },
+        rendered,
+    }
+}
+
+/*
+    One file per operation, the content comes from the template in dir_spec and the name from `file_name`.
+*/
+pub struct TemplateGenerator {
+    pub options: TemplateOptions,
+}
+
+impl Generator for TemplateGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { dir_spec, prefix, spec, methods } = input;
+        let template_path = format!("{dir_spec}{}", self.options.template);
+
+        let template = std::fs::read_to_string(&template_path).map_err(|err| {
+            ErrorProcess::message(format!("error read content {template_path} -> {err}"))
+        })?;
+
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+
+        env.add_template("file", &template).map_err(|err| {
+            ErrorProcess::message(format!("template {template_path}: {err}"))
+        })?;
+
+        env.add_template("file_name", &self.options.file_name).map_err(|err| {
+            ErrorProcess::message(format!("template file_name {}: {err}", self.options.file_name))
+        })?;
+
+        let mut out = Vec::new();
+        let mut used = HashMap::<String, &String>::new();          //file_name -> method_name
+
+        for (method_name, OperationRef { url, method }) in methods.iter() {
+            let handler = spec.get_handler(url, method)?;
+            let context = get_context(prefix, method_name, url, method, handler, &self.options);
+
+            let content = env.get_template("file").and_then(|template| template.render(&context)).map_err(|err| {
+                ErrorProcess::message(format!("template {template_path}, {method_name}: {err}"))
+            })?;
+
+            let file_name = env.get_template("file_name").and_then(|template| template.render(&context)).map_err(|err| {
+                ErrorProcess::message(format!("template file_name, {method_name}: {err}"))
+            })?;
+
+            if file_name.starts_with('/') || file_name.split('/').any(|chunk| chunk == "..") {
+                return Err(ErrorProcess::message(format!("template file_name, {method_name}: {file_name} is outside of dir_target")));
+            }
+
+            if let Some(other) = used.insert(file_name.clone(), method_name) {
+                let mut names = [other, method_name];
+                names.sort();
+                let [first, second] = names;
+                return Err(ErrorProcess::message(format!("template file_name: {first} and {second} are both rendered to {file_name}")));
+            }
+
+            out.push(RenderedFile { file_name, content });
+        }
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::open_api_spec::SpecHandlerType;
+use super::generate_params_type::{generate_params_type, generate_type_ts};
+use super::{generate_generic_response, to_big_camel_case};
+
+/*
+    Only the type declarations, without any runtime code or imports, so the file is a valid .d.ts
+*/
+pub fn generate_types_only(name_in_file: &str, handler: &SpecHandlerType) -> String {
+    let name_in_file_camelcase_big = to_big_camel_case(name_in_file);
+
+    let mut out = vec!(
+        "//The contents of this file have been generated automatically. Do not edit this file.".to_string(),
+        "".into(),
+        generate_params_type(handler),
+        "".into(),
+    );
+
+    let responses = handler.responses.get_sorted();
+
+    for (code, response) in responses.iter() {
+        let type_ts = generate_type_ts(0, response);
+        out.push(format!("export type Response{code}Type = {type_ts};"));
+        out.push("".into());
+    }
+
+    out.push(format!("export type {name_in_file_camelcase_big}ParamsType = ParamsType;"));
+    out.push("".into());
+
+    if !responses.is_empty() {
+        let (generic_response_types, _) = generate_generic_response(&handler.responses);
+        out.push(format!("export type {name_in_file_camelcase_big}ResponseType = {generic_response_types};"));
+        out.push("".into());
+    }
+
+    if responses.iter().any(|(code, _)| **code == 200) {
+        out.push(format!("export type {name_in_file_camelcase_big}Response200Type = Response200Type;"));
+        out.push("".into());
+    }
+
+    out.join("\n")
+}
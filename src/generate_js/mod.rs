@@ -1,7 +1,10 @@
 use std::collections::VecDeque;
 
 use crate::generate_js::fix_to_camel_case::fix_to_camel_case;
-use crate::open_api_spec::{OpenApiMethod, ParamIn, SpecHandlerType};
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{ApiKeyIn, OpenApiMethod, OperationRef, ParamIn, SecuritySchemeType, SpecHandlerType};
+use crate::output::RenderedFile;
+use crate::read_wanted_spec::{CodecType, RuntimeConfig, RuntimeImports, TsOptions};
 use crate::utils::ErrorProcess;
 use crate::utils::OrderHashMap;
 use crate::open_api_type::OpenApiType;
@@ -9,7 +12,15 @@ use crate::open_api_type::OpenApiType;
 mod generate_ident;
 mod generate_params_type;
 mod generate_response_io;
-mod fix_to_camel_case;
+mod generate_response_zod;
+mod generate_types_only;
+mod generate_runtime;
+mod generate_template;
+mod generate_index;
+
+pub use generate_template::TemplateGenerator;
+pub mod fix_to_camel_case;
+mod generate_auth;
 
 fn add_import_query_string(spec: &SpecHandlerType) -> &str {
     for param in spec.parameters.iter() {
@@ -18,6 +29,12 @@ fn add_import_query_string(spec: &SpecHandlerType) -> &str {
         }
     }
 
+    for security in spec.security.iter() {
+        if let SecuritySchemeType::ApiKey { where_in: ApiKeyIn::Query, .. } = security.scheme {
+            return "import qs from 'query-string';";
+        }
+    }
+
     return "";
 }
 
@@ -37,7 +54,7 @@ fn test_word_first_letter_to_lowercase() {
     assert_eq!(word_first_letter_to_lowercase(""), "");
 }
 
-fn word_first_letter_to_upper(word: &str) -> String {
+pub fn word_first_letter_to_upper(word: &str) -> String {
     let mut chars = word.chars().collect::<Vec<char>>();
     if let Some(char) = chars.get_mut(0) {
         *char = char.to_uppercase().nth(0).unwrap();
@@ -53,7 +70,7 @@ fn test_word_first_letter_to_upper() {
     assert_eq!(word_first_letter_to_upper(""), "");
 }
 
-fn to_big_camel_case(name: &str) -> String {
+pub fn to_big_camel_case(name: &str) -> String {
     name.split("_")
         .map(|word| word_first_letter_to_upper(word))
         .collect::<String>()
@@ -106,14 +123,34 @@ fn generate_generic_response(responses: &OrderHashMap<u16, OpenApiType>) -> (Str
 }
 
 
-pub fn generate_js(name_in_file: String, url: String, method: OpenApiMethod, handler: &SpecHandlerType) -> Result<String, ErrorProcess> {
+/*
+    (the imports of the library, the decoders of the responses)
+*/
+fn generate_codecs(handler: &SpecHandlerType, url: &String, method: &OpenApiMethod, codec: CodecType) -> (String, String) {
+    let left = '{';
+    let right = '}';
+
+    match codec {
+        CodecType::IoTs => (
+            format!("import * as t from 'io-ts';\nimport {left} isRight {right} from 'fp-ts/lib/Either';"),
+            generate_response_io::generate_response_io(handler, url, method),
+        ),
+        CodecType::Zod => (
+            format!("import {left} z {right} from 'zod';"),
+            generate_response_zod::generate_response_zod(handler, url, method),
+        ),
+    }
+}
+
+fn generate_js(name_in_file: String, url: String, method: OpenApiMethod, handler: &SpecHandlerType, codec: CodecType, imports: &RuntimeImports) -> Result<String, ErrorProcess> {
     let left = '{';
     let right = '}';
 
     let import_query_string = add_import_query_string(handler);
     let generate_params_type = generate_params_type::generate_params_type(handler);
-    let generate_response_io_data = generate_response_io::generate_response_io(handler, &url, &method);
-    let generate_url = generate_url(url, handler);
+    let (import_validator, generate_response_io_data) = generate_codecs(handler, &url, &method, codec);
+    let auth = generate_auth::generate_auth(handler);
+    let generate_url = generate_url(url, handler, &auth.query);
     let generate_method = get_method(&method);
     let generate_body = get_body(handler);
     let generate_params_name = get_params_name(handler);
@@ -121,14 +158,26 @@ pub fn generate_js(name_in_file: String, url: String, method: OpenApiMethod, han
     let name_in_file_camelcase_big = to_big_camel_case(name_in_file.as_str());
     let name_in_file_camelcase_small = word_first_letter_to_lowercase(name_in_file_camelcase_big.as_str());
     let (generic_response_types, generic_response_ifs) = generate_generic_response(&handler.responses);
+    let auth_args_decl = auth.args_decl;
+    let auth_args_call = auth.args_call;
+    let auth_fetch_params = auth.fetch_params;
+
+    let RuntimeImports { fetch_path, fetch_name, fetch_response_type, time_log_path, time_log_name, json_parse_path, json_parse_name } = imports;
+
+    let (import_time_log, time_log_create, time_log_show) = match time_log_path {
+        Some(time_log_path) => (
+            format!("import {left} {time_log_name} {right} from '{time_log_path}';\n"),
+            format!("    const apiTime = {time_log_name}.createWithProcessEnv(method, url);\n"),
+            "    apiTime.show(response.status);\n".to_string(),
+        ),
+        None => (String::new(), String::new(), String::new()),
+    };
 
     let content = format!(r#"//The contents of this file have been generated automatically. Do not edit this file.
 
-import * as t from 'io-ts';
-import {left} isRight {right} from 'fp-ts/lib/Either';
-import {left} fetchGeneralRaw, FetchGeneralRawResponseType {right} from 'src_common/common/fetch';
-import {left} ApiTimeLog {right} from 'src_common/server/webDriver/logFormat';
-import {left} jsonParse {right} from 'src_common/common/jsonParse';
+{import_validator}
+import {left} {fetch_name}, {fetch_response_type} {right} from '{fetch_path}';
+{import_time_log}import {left} {json_parse_name} {right} from '{json_parse_path}';
 {import_query_string}
 
 
@@ -138,20 +187,17 @@ import {left} jsonParse {right} from 'src_common/common/jsonParse';
 {generate_response_io_data}
 
 
-export const {name_in_file} = async (api_url: string, api_timeout: number, backendToken: string, {generate_params_name}: ParamsType): Promise<FetchGeneralRawResponseType> => {left}
+export const {name_in_file} = async (api_url: string, api_timeout: number{auth_args_decl}, {generate_params_name}: ParamsType): Promise<{fetch_response_type}> => {left}
     const url = `${left}api_url{right}{generate_url}`;
     const method = {generate_method};
     const paramsFetch = {left}
         url,
         body: {generate_body},
-        backendToken,
-        timeout: api_timeout,
+{auth_fetch_params}        timeout: api_timeout,
     {right};
 
-    const apiTime = ApiTimeLog.createWithProcessEnv(method, url);
-    const response = await fetchGeneralRaw(method, paramsFetch);
-    apiTime.show(response.status);
-    return response;
+{time_log_create}    const response = await {fetch_name}(method, paramsFetch);
+{time_log_show}    return response;
 {right};
 
 
@@ -161,10 +207,10 @@ export type {name_in_file_camelcase_big}ResponseType = {generic_response_types};
 
 export type {name_in_file_camelcase_big}Response200Type = Response200Type;
 
-export const {name_in_file_camelcase_small}Request = async (api_url: string, api_timeout: number, backendToken: string, params: ParamsType): Promise<{name_in_file_camelcase_big}ResponseType> => {left}
-    const response = await {name_in_file}(api_url, api_timeout, backendToken, params);
+export const {name_in_file_camelcase_small}Request = async (api_url: string, api_timeout: number{auth_args_decl}, params: ParamsType): Promise<{name_in_file_camelcase_big}ResponseType> => {left}
+    const response = await {name_in_file}(api_url, api_timeout{auth_args_call}, params);
     const {left} status, body {right} = response;
-    const bodyParsed = jsonParse(body);
+    const bodyParsed = {json_parse_name}(body);
 
     if (bodyParsed.type === 'text') {left}
         throw Error(`Http status ${left}status{right} - json was expected`);
@@ -180,6 +226,117 @@ export const {name_in_file_camelcase_small}Request = async (api_url: string, api
     Ok(content)
 }
 
+/*
+    The helpers generated with RuntimeConfig::NativeFetch are imported from ./openapi_{prefix}_runtime
+*/
+fn get_runtime_imports(prefix: &String, runtime: &RuntimeConfig) -> RuntimeImports {
+    match runtime {
+        RuntimeConfig::Imports(imports) => imports.clone(),
+        RuntimeConfig::NativeFetch => {
+            let runtime_path = format!("./openapi_{prefix}_runtime");
+
+            RuntimeImports {
+                fetch_path: runtime_path.clone(),
+                fetch_name: "fetchGeneralRaw".into(),
+                fetch_response_type: "FetchGeneralRawResponseType".into(),
+                time_log_path: None,
+                time_log_name: String::new(),
+                json_parse_path: runtime_path,
+                json_parse_name: "jsonParse".into(),
+            }
+        }
+    }
+}
+
+/*
+    One file per operation: openapi_{prefix}_{method_name}.ts
+    With RuntimeConfig::NativeFetch also openapi_{prefix}_runtime.ts
+    With `index` also openapi_{prefix}_index.ts
+*/
+fn generate_files(input: &GeneratorInput, codec: CodecType, options: &TsOptions) -> Result<Vec<RenderedFile>, ErrorProcess> {
+    let GeneratorInput { prefix, spec, methods, .. } = input;
+    let imports = get_runtime_imports(prefix, &options.runtime);
+    let mut out = Vec::new();
+
+    for (method_name, OperationRef { url, method }) in methods.iter() {
+        let name_in_file = format!("openapi_{prefix}_{method_name}");
+        let file_name = format!("{name_in_file}.ts");
+
+        let handler = spec.get_handler(url, method)?;
+
+        let content = generate_js(name_in_file, url.clone(), method.clone(), handler, codec, &imports)?;
+        out.push(RenderedFile { file_name, content });
+    }
+
+    if let RuntimeConfig::NativeFetch = options.runtime {
+        if methods.contains_key("runtime") {
+            return Err(ErrorProcess::message(format!("{prefix}: the method name 'runtime' is reserved for openapi_{prefix}_runtime.ts")));
+        }
+
+        out.push(RenderedFile {
+            file_name: format!("openapi_{prefix}_runtime.ts"),
+            content: generate_runtime::generate_runtime(),
+        });
+    }
+
+    if options.index {
+        if methods.contains_key("index") {
+            return Err(ErrorProcess::message(format!("{prefix}: the method name 'index' is reserved for openapi_{prefix}_index.ts")));
+        }
+
+        out.push(RenderedFile {
+            file_name: format!("openapi_{prefix}_index.ts"),
+            content: generate_index::generate_index(prefix, spec, methods)?,
+        });
+    }
+
+    Ok(out)
+}
+
+pub struct IoTsGenerator {
+    pub options: TsOptions,
+}
+
+impl Generator for IoTsGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        generate_files(input, CodecType::IoTs, &self.options)
+    }
+}
+
+pub struct ZodGenerator {
+    pub options: TsOptions,
+}
+
+impl Generator for ZodGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        generate_files(input, CodecType::Zod, &self.options)
+    }
+}
+
+/*
+    One declaration file per operation: openapi_{prefix}_{method_name}.d.ts
+*/
+pub struct TypesOnlyGenerator;
+
+impl Generator for TypesOnlyGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { prefix, spec, methods, .. } = input;
+        let mut out = Vec::new();
+
+        for (method_name, OperationRef { url, method }) in methods.iter() {
+            let name_in_file = format!("openapi_{prefix}_{method_name}");
+            let file_name = format!("{name_in_file}.d.ts");
+
+            let handler = spec.get_handler(url, method)?;
+
+            let content = generate_types_only::generate_types_only(&name_in_file, handler);
+            out.push(RenderedFile { file_name, content });
+        }
+
+        Ok(out)
+    }
+}
+
 fn get_params_name(handler: &SpecHandlerType) -> String {
     if handler.parameters.len() > 0 {
         return "params".to_string();
@@ -193,7 +350,7 @@ fn get_method(method: &OpenApiMethod) -> String {
     format!("'{method}'")
 }
 
-fn generate_url(url: String, spec: &SpecHandlerType) -> String {
+fn generate_url(url: String, spec: &SpecHandlerType, auth_query: &[String]) -> String {
     /*
         convert url:
         /website-cms/{universe}/landing/landing_promo_page
@@ -202,15 +359,19 @@ fn generate_url(url: String, spec: &SpecHandlerType) -> String {
 
     let base_url = url.split('/').map(generate_url_item).collect::<Vec<String>>().join("/");
 
-    let mut query_params = Vec::<&String>::new();
+    let mut query_params = Vec::<String>::new();
 
     for param in spec.parameters.iter() {
         if param.where_in == ParamIn::Query {
-            query_params.push(&param.name);
+            let param_name = &param.name;
+            let param_name_camel_case = fix_to_camel_case(param_name);
+            query_params.push(format!("'{param_name}': params.{param_name_camel_case}"));
         }
     }
 
-    if query_params.len() > 0 {
+    query_params.extend(auth_query.iter().cloned());
+
+    if !query_params.is_empty() {
         let query = generate_url_query(query_params);
         return format!("{base_url}?{query}");
 
@@ -219,18 +380,11 @@ fn generate_url(url: String, spec: &SpecHandlerType) -> String {
     }
 }
 
-fn generate_url_query(query_params: Vec<&String>) -> String {
+fn generate_url_query(query_params: Vec<String>) -> String {
     let left = '{';
     let right = '}';
 
-    let mut param_chunks = Vec::<String>::new();
-
-    for param_name in query_params {
-        let param_name_camel_case = fix_to_camel_case(&param_name);
-        param_chunks.push(format!("'{param_name}': params.{param_name_camel_case}"));
-    }
-
-    let param_result = param_chunks.join(", ");
+    let param_result = query_params.join(", ");
 
     format!("${left}qs.stringify({left} {param_result} {right}, {left} skipNull: true {right}){right}")
 }
@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value, json};
+
+use crate::generate_js::fix_to_camel_case::fix_to_camel_case;
+use crate::generate_js::to_big_camel_case;
+use crate::generate_rust::to_type_name;
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{OperationRef, ParamIn, SpecHandlerType};
+use crate::open_api_type::OpenApiType;
+use crate::output::RenderedFile;
+use crate::read_wanted_spec::{JsonSchemaOptions, SchemaRefs};
+use crate::utils::ErrorProcess;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/*
+    Builds the schema of one file, with SchemaRefs::Defs the nested objects are collected in `defs`
+*/
+struct SchemaBuilder {
+    refs: SchemaRefs,
+    defs: BTreeMap<String, Value>,
+}
+
+/*
+    A value which is not required may also be null, the same as null | undefined in TS.
+    The null of a union is added to its "anyOf".
+*/
+fn add_require(required: bool, mut schema: Value) -> Value {
+    if required {
+        return schema;
+    }
+
+    let type_null = json!({ "type": "null" });
+
+    if let Some(Value::Array(list)) = schema.get_mut("anyOf") {
+        list.push(type_null);
+        return schema;
+    }
+
+    json!({ "anyOf": [schema, type_null] })
+}
+
+impl SchemaBuilder {
+    fn new(refs: SchemaRefs) -> SchemaBuilder {
+        SchemaBuilder {
+            refs,
+            defs: BTreeMap::new(),
+        }
+    }
+
+    /*
+        `name` names the definition in "$defs", the nested objects are named after their path
+    */
+    fn get_schema(&mut self, name: &str, type_param: &OpenApiType) -> Value {
+        match type_param {
+            OpenApiType::LiteralString { required, value } => add_require(*required, json!({ "type": "string", "const": value })),
+            OpenApiType::String { required } => add_require(*required, json!({ "type": "string" })),
+            OpenApiType::Number { required } => add_require(*required, json!({ "type": "number" })),
+            OpenApiType::Boolean { required } => add_require(*required, json!({ "type": "boolean" })),
+            OpenApiType::Unknown => json!({}),
+            OpenApiType::Array { required, items } => {
+                let items = self.get_schema(&format!("{name}Item"), items);
+                add_require(*required, json!({ "type": "array", "items": items }))
+            },
+            OpenApiType::Record { required, value } => {
+                let value = self.get_schema(&format!("{name}Value"), value);
+                add_require(*required, json!({ "type": "object", "additionalProperties": value }))
+            },
+            OpenApiType::Object { required, props } => {
+                let props = props.get_sorted();
+                let schema = self.get_object(name, props.iter().map(|(key, value)| (key.to_string(), *value)).collect());
+                let schema = self.add_def(name, schema);
+                add_require(*required, schema)
+            },
+            OpenApiType::Union { required, list } => {
+                let schema = self.get_union(name, list);
+                add_require(*required, schema)
+            },
+        }
+    }
+
+    fn get_object(&mut self, name: &str, props: Vec<(String, &OpenApiType)>) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::<Value>::new();
+
+        for (key, value) in props {
+            let schema = self.get_schema(&format!("{name}{}", to_type_name(&key)), value);
+
+            if value.is_required() {
+                required.push(Value::String(key.clone()));
+            }
+
+            properties.insert(key, schema);
+        }
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+
+    /*
+        'a' | 'b'         -> { "type": "string", "enum": ["a", "b"] }
+        string | { ... }  -> { "anyOf": [...] }
+    */
+    fn get_union(&mut self, name: &str, list: &[OpenApiType]) -> Value {
+        let literals = list
+            .iter()
+            .map(|item| match item {
+                OpenApiType::LiteralString { value, required: true } => Some(Value::String(value.clone())),
+                _ => None,
+            })
+            .collect::<Option<Vec<Value>>>();
+
+        match (list, literals) {
+            ([], _) => json!({ "not": {} }),
+            ([item], _) => self.get_schema(name, item),
+            (_, Some(literals)) => json!({ "type": "string", "enum": literals }),
+            (list, None) => {
+                let list = list
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.get_schema(&format!("{name}Variant{}", index + 1), item))
+                    .collect::<Vec<Value>>();
+
+                json!({ "anyOf": list })
+            }
+        }
+    }
+
+    /*
+        With SchemaRefs::Defs the object goes to "$defs" and a reference is returned
+    */
+    fn add_def(&mut self, name: &str, schema: Value) -> Value {
+        if self.refs == SchemaRefs::Inline {
+            return schema;
+        }
+
+        let mut def_name = name.to_string();
+        let mut index = 2;
+
+        while self.defs.get(&def_name).is_some_and(|def| *def != schema) {
+            def_name = format!("{name}{index}");
+            index += 1;
+        }
+
+        let reference = format!("#/$defs/{def_name}");
+        self.defs.insert(def_name, schema);
+        json!({ "$ref": reference })
+    }
+
+    /*
+        The root of the file is never a reference, it gets the "$schema", the "title" and the collected "$defs"
+    */
+    fn into_file(mut self, title: &str, root: Value) -> Value {
+        let root = match root.get("$ref").and_then(|reference| reference.as_str()) {
+            Some(reference) => {
+                let def_name = reference.trim_start_matches("#/$defs/").to_string();
+                self.defs.remove(&def_name).unwrap_or(root)
+            },
+            None => root,
+        };
+
+        let mut out = Map::new();
+        out.insert("$schema".into(), json!(SCHEMA_DIALECT));
+        out.insert("title".into(), json!(title));
+
+        if let Value::Object(root) = root {
+            out.extend(root);
+        }
+
+        if !self.defs.is_empty() {
+            out.insert("$defs".into(), json!(self.defs));
+        }
+
+        Value::Object(out)
+    }
+}
+
+/*
+    The same fields as ParamsType of the TS output: path, query and body, the names in camelCase
+*/
+fn get_params_schema(refs: SchemaRefs, title: &str, handler: &SpecHandlerType) -> Value {
+    let mut builder = SchemaBuilder::new(refs);
+
+    let params = handler.parameters
+        .iter()
+        .filter(|param| param.where_in != ParamIn::Header)
+        .map(|param| (fix_to_camel_case(&param.name), &param.api_type))
+        .collect();
+
+    let root = builder.get_object(title, params);
+    builder.into_file(title, root)
+}
+
+fn get_response_schema(refs: SchemaRefs, title: &str, response: &OpenApiType) -> Value {
+    let mut builder = SchemaBuilder::new(refs);
+    let root = builder.get_schema(title, response);
+    builder.into_file(title, root)
+}
+
+#[test]
+fn test_get_response_schema_defs() {
+    use crate::utils::OrderHashMap;
+
+    let mut item = OrderHashMap::new();
+    item.expect_insert("id".to_string(), OpenApiType::Number { required: true }).unwrap();
+
+    let mut props = OrderHashMap::new();
+    props.expect_insert("items".to_string(), OpenApiType::Array {
+        required: true,
+        items: Box::new(OpenApiType::Object { required: true, props: item }),
+    }).unwrap();
+    props.expect_insert("name".to_string(), OpenApiType::String { required: false }).unwrap();
+
+    let response = OpenApiType::Object { required: true, props };
+    let schema = get_response_schema(SchemaRefs::Defs, "Draw", &response);
+
+    assert_eq!(schema, json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "Draw",
+        "type": "object",
+        "properties": {
+            "items": { "type": "array", "items": { "$ref": "#/$defs/DrawItemsItem" } },
+            "name": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+        },
+        "required": ["items"],
+        "$defs": {
+            "DrawItemsItem": {
+                "type": "object",
+                "properties": { "id": { "type": "number" } },
+                "required": ["id"],
+            },
+        },
+    }));
+}
+
+/*
+    Per operation:
+    openapi_{prefix}_{method_name}.params.schema.json
+    openapi_{prefix}_{method_name}.response{code}.schema.json
+*/
+pub struct JsonSchemaGenerator {
+    pub options: JsonSchemaOptions,
+}
+
+impl Generator for JsonSchemaGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { prefix, spec, methods, .. } = input;
+        let refs = self.options.refs;
+        let mut out = Vec::new();
+
+        for (method_name, OperationRef { url, method }) in methods.iter() {
+            let name_in_file = format!("openapi_{prefix}_{method_name}");
+            let name_in_file_camelcase_big = to_big_camel_case(&name_in_file);
+
+            let handler = spec.get_handler(url, method)?;
+
+            let params = get_params_schema(refs, &format!("{name_in_file_camelcase_big}Params"), handler);
+            out.push(RenderedFile {
+                file_name: format!("{name_in_file}.params.schema.json"),
+                content: format!("{}\n", serde_json::to_string_pretty(&params)?),
+            });
+
+            for (code, response) in handler.responses.get_sorted() {
+                let schema = get_response_schema(refs, &format!("{name_in_file_camelcase_big}Response{code}"), response);
+                out.push(RenderedFile {
+                    file_name: format!("{name_in_file}.response{code}.schema.json"),
+                    content: format!("{}\n", serde_json::to_string_pretty(&schema)?),
+                });
+            }
+        }
+
+        Ok(out)
+    }
+}
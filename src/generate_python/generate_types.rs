@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use crate::open_api_type::OpenApiType;
+use super::{to_python_string, to_snake_case, unique};
+use crate::generate_rust::to_type_name;
+
+/*
+    Collects the pydantic models and the type aliases of one file.
+    The nested models are named after their path and declared before the model which uses them:
+    GetDrawResponse200 { items: [{ ... }] } -> GetDrawResponse200ItemsItem, GetDrawResponse200
+*/
+pub struct PythonTypes {
+    defs: Vec<String>,
+    names: HashSet<String>,
+}
+
+fn wrap_optional(required: bool, type_python: String) -> String {
+    match required {
+        true => type_python,
+        false => format!("Optional[{type_python}]"),
+    }
+}
+
+impl PythonTypes {
+    pub fn new() -> PythonTypes {
+        PythonTypes {
+            defs: Vec::new(),
+            names: HashSet::new(),
+        }
+    }
+
+    pub fn new_name(&mut self, name: &str) -> String {
+        unique(&mut self.names, name.to_string())
+    }
+
+    pub fn add_def(&mut self, def: String) {
+        self.defs.push(def);
+    }
+
+    pub fn get_defs(&self) -> String {
+        self.defs.join("\n\n\n")
+    }
+
+    /*
+        Declares `{name} = ...` when the type is not a model with that name.
+    */
+    pub fn add_named(&mut self, name: &str, type_param: &OpenApiType) -> String {
+        let type_python = self.get_type(name, type_param);
+
+        if type_python != name {
+            let name = self.new_name(name);
+            self.defs.push(format!("{name} = {type_python}"));
+            return name;
+        }
+
+        type_python
+    }
+
+    /*
+        The annotation of a value, with Optional[...] when the value is not required.
+        The models are declared on the way, `name` is a suggestion for their name.
+    */
+    pub fn get_type(&mut self, name: &str, type_param: &OpenApiType) -> String {
+        match type_param {
+            OpenApiType::LiteralString { required, value } => wrap_optional(*required, format!("Literal[{}]", to_python_string(value))),
+            OpenApiType::String { required } => wrap_optional(*required, "str".into()),
+            OpenApiType::Number { required } => wrap_optional(*required, "float".into()),
+            OpenApiType::Boolean { required } => wrap_optional(*required, "bool".into()),
+            OpenApiType::Unknown => "Any".into(),
+            OpenApiType::Array { required, items } => {
+                let items = self.get_type(&format!("{name}Item"), items);
+                wrap_optional(*required, format!("list[{items}]"))
+            },
+            OpenApiType::Record { required, value } => {
+                let value = self.get_type(&format!("{name}Value"), value);
+                wrap_optional(*required, format!("dict[str, {value}]"))
+            },
+            OpenApiType::Object { required, props } => {
+                let type_python = self.add_model(name, props.get_sorted());
+                wrap_optional(*required, type_python)
+            },
+            OpenApiType::Union { required, list } => {
+                let type_python = self.get_union(name, list);
+                wrap_optional(*required, type_python)
+            },
+        }
+    }
+
+    fn add_model(&mut self, name: &str, props: Vec<(&String, &OpenApiType)>) -> String {
+        let name = self.new_name(name);
+        let mut fields = HashSet::new();
+        let mut lines = Vec::<String>::new();
+
+        for (key, value) in props {
+            let field = unique(&mut fields, to_snake_case(key));
+            let type_python = self.get_type(&format!("{name}{}", to_type_name(key)), value);
+
+            let default = match (field == *key, value.is_required()) {
+                (true, true) => String::new(),
+                (true, false) => " = None".into(),
+                (false, true) => format!(" = Field(alias={})", to_python_string(key)),
+                (false, false) => format!(" = Field(default=None, alias={})", to_python_string(key)),
+            };
+
+            lines.push(format!("    {field}: {type_python}{default}"));
+        }
+
+        let mut out = vec!(format!("class {name}(BaseModel):"));
+
+        if lines.iter().any(|line| line.contains("alias=")) {
+            out.push("    model_config = ConfigDict(populate_by_name=True)".into());
+            out.push("".into());
+        }
+
+        if lines.is_empty() {
+            out.push("    pass".into());
+        }
+
+        out.extend(lines);
+        self.defs.push(out.join("\n"));
+        name
+    }
+
+    /*
+        'a' | 'b'         -> Literal["a", "b"]
+        string | { ... }  -> Union[str, Model]
+    */
+    fn get_union(&mut self, name: &str, list: &[OpenApiType]) -> String {
+        let literals = list
+            .iter()
+            .map(|item| match item {
+                OpenApiType::LiteralString { value, required: true } => Some(to_python_string(value)),
+                _ => None,
+            })
+            .collect::<Option<Vec<String>>>();
+
+        match (list, literals) {
+            ([], _) => "Any".into(),
+            ([item], _) => self.get_type(name, item),
+            (_, Some(literals)) => {
+                let literals = literals.join(", ");
+                format!("Literal[{literals}]")
+            },
+            (list, None) => {
+                let items = list
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.get_type(&format!("{name}Variant{}", index + 1), item))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                format!("Union[{items}]")
+            }
+        }
+    }
+}
+
+#[test]
+fn test_python_types_object() {
+    use crate::utils::OrderHashMap;
+
+    let mut props = OrderHashMap::new();
+    props.expect_insert("pageSize".to_string(), OpenApiType::Number { required: false }).unwrap();
+    props.expect_insert("lang".to_string(), OpenApiType::Union {
+        required: true,
+        list: vec!(
+            OpenApiType::LiteralString { required: true, value: "pl".into() },
+            OpenApiType::LiteralString { required: true, value: "en".into() },
+        ),
+    }).unwrap();
+
+    let mut types = PythonTypes::new();
+    let type_param = OpenApiType::Object { required: true, props };
+
+    assert_eq!(types.get_type("Info", &type_param), "Info");
+    assert_eq!(types.get_defs(), [
+        "class Info(BaseModel):",
+        "    model_config = ConfigDict(populate_by_name=True)",
+        "",
+        "    lang: Literal['pl', 'en']",
+        "    page_size: Optional[float] = Field(default=None, alias='pageSize')",
+    ].join("\n"));
+}
+
+#[test]
+fn test_python_types_field_names() {
+    use crate::utils::OrderHashMap;
+
+    let mut props = OrderHashMap::new();
+    props.expect_insert("2fa".to_string(), OpenApiType::Boolean { required: true }).unwrap();
+    props.expect_insert("model_config".to_string(), OpenApiType::String { required: false }).unwrap();
+
+    let mut types = PythonTypes::new();
+    types.get_type("Login", &OpenApiType::Object { required: true, props });
+
+    //not valid identifiers, or reserved by pydantic -> a prefixed name with the original key as the alias
+    assert_eq!(types.get_defs(), [
+        "class Login(BaseModel):",
+        "    model_config = ConfigDict(populate_by_name=True)",
+        "",
+        "    field_2fa: bool = Field(alias='2fa')",
+        "    field_model_config: Optional[str] = Field(default=None, alias='model_config')",
+    ].join("\n"));
+}
@@ -0,0 +1,392 @@
+use std::collections::HashSet;
+
+use crate::generate_rust::{split_words, to_type_name};
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{ApiKeyIn, OpenApiMethod, OperationRef, ParamIn, SecuritySchemeType, SpecHandlerType};
+use crate::output::RenderedFile;
+use crate::utils::ErrorProcess;
+
+mod generate_types;
+
+use generate_types::PythonTypes;
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+//the attributes of BaseModel, a field with such a name would shadow them, the model_* names are handled separately
+const RESERVED: &[&str] = &[
+    "copy", "dict", "json", "schema", "construct", "validate", "fields", "schema_json", "parse_obj", "parse_raw",
+    "parse_file", "from_orm", "update_forward_refs",
+];
+
+fn unique(used: &mut HashSet<String>, name: String) -> String {
+    let mut out = name.clone();
+    let mut index = 2;
+
+    while used.contains(&out) {
+        out = format!("{name}{index}");
+        index += 1;
+    }
+
+    used.insert(out.clone());
+    out
+}
+
+/*
+    hello 'world' -> 'hello \'world\''
+*/
+fn to_python_string(value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('\'', "\\'");
+    format!("'{value}'")
+}
+
+#[test]
+fn test_to_python_string() {
+    assert_eq!(to_python_string("pl"), "'pl'");
+    assert_eq!(to_python_string("it's"), "'it\\'s'");
+}
+
+/*
+    A valid name of a field, a function or an argument: pageSize -> page_size, from -> from_
+    Pydantic rejects the fields with a leading underscore and reserves the "model_" prefix:
+    2fa -> field_2fa, model_config -> field_model_config
+*/
+fn to_snake_case(name: &str) -> String {
+    let words = split_words(name).into_iter().map(|word| word.to_lowercase()).collect::<Vec<String>>();
+    let out = words.join("_");
+
+    if out.is_empty() {
+        return "field".into();
+    }
+
+    if out.starts_with(|char: char| char.is_numeric()) || out.starts_with("model_") {
+        return format!("field_{out}");
+    }
+
+    if KEYWORDS.contains(&out.as_str()) || RESERVED.contains(&out.as_str()) {
+        return format!("{out}_");
+    }
+
+    out
+}
+
+#[test]
+fn test_to_snake_case() {
+    assert_eq!(to_snake_case("getCurrentDraw"), "get_current_draw");
+    assert_eq!(to_snake_case("page-size"), "page_size");
+    assert_eq!(to_snake_case("from"), "from_");
+    assert_eq!(to_snake_case("json"), "json_");
+    assert_eq!(to_snake_case("2fa"), "field_2fa");
+    assert_eq!(to_snake_case("modelConfig"), "field_model_config");
+}
+
+/*
+    Shared by the functions of the operations
+*/
+const CLIENT_RUNTIME: &str = r#"import base64
+import json
+from typing import Any, Literal, Optional, Union
+
+import httpx
+import pydantic_core
+from pydantic import BaseModel, ConfigDict, Field, TypeAdapter
+
+
+class OpenapiError(Exception):
+    def __init__(self, response: httpx.Response):
+        super().__init__(f'Unhandled response, status {response.status_code}')
+        self.response = response
+
+
+def _jsonable(value: Any) -> Any:
+    return pydantic_core.to_jsonable_python(value, by_alias=True, exclude_none=True)
+
+
+def _text(value: Any) -> list[str]:
+    '''The parameters are sent as text, None is skipped and a list is sent as a repeated parameter'''
+    value = _jsonable(value)
+
+    if value is None:
+        return []
+    if isinstance(value, list):
+        return [text for item in value for text in _text(item)]
+    if isinstance(value, bool):
+        return ['true' if value else 'false']
+    if isinstance(value, float) and value.is_integer():
+        return [str(int(value))]
+    if isinstance(value, dict):
+        return [json.dumps(value)]
+    return [str(value)]
+
+
+def _path_value(value: Any) -> str:
+    return ','.join(_text(value))
+
+
+def _query(values: dict[str, Any]) -> list[tuple[str, str]]:
+    return [(name, text) for name, value in values.items() for text in _text(value)]
+
+
+def _headers(values: dict[str, Any]) -> dict[str, str]:
+    return {name: ','.join(_text(value)) for name, value in values.items() if _text(value)}
+
+
+def _basic(credentials: tuple[str, str]) -> str:
+    username, password = credentials
+    token = base64.b64encode(f'{username}:{password}'.encode()).decode()
+    return f'Basic {token}'
+
+
+def _decode(type_: Any, response: httpx.Response) -> Any:
+    return TypeAdapter(type_).validate_json(response.content or b'null')"#;
+
+struct AuthPython {
+    args: Vec<(String, String)>,        //(name, annotation), keyword-only arguments
+    headers: Vec<String>,               //"'X-Api-Key': api_key"
+    query: Vec<String>,
+}
+
+/*
+    With the optional authentication the arguments default to None, _headers and _query skip the None values
+*/
+fn generate_auth(handler: &SpecHandlerType) -> AuthPython {
+    let optional = handler.security_optional;
+
+    let annotation = |type_python: &str| match optional {
+        true => format!("Optional[{type_python}] = None"),
+        false => type_python.to_string(),
+    };
+
+    let when_given = |args: &[&String], value: String| match optional {
+        true => {
+            let condition = args.iter().map(|arg| format!("{arg} is None")).collect::<Vec<String>>().join(" or ");
+            format!("None if {condition} else {value}")
+        },
+        false => value,
+    };
+
+    let mut args = Vec::<(String, String)>::new();
+    let mut headers = Vec::<String>::new();
+    let mut query = Vec::<String>::new();
+    let mut cookies = Vec::<(String, String)>::new();
+
+    for security in handler.security.iter() {
+        let arg = to_snake_case(&security.name);
+
+        match &security.scheme {
+            SecuritySchemeType::Bearer => {
+                if args.iter().any(|(name, _)| name == "backend_token") {
+                    continue;
+                }
+
+                let backend_token = "backend_token".to_string();
+                args.push((backend_token.clone(), annotation("str")));
+                headers.push(format!("'Authorization': {}", when_given(&[&backend_token], "f'Bearer {backend_token}'".into())));
+            },
+            SecuritySchemeType::Basic => {
+                args.push((arg.clone(), annotation("tuple[str, str]")));
+                headers.push(format!("'Authorization': {}", when_given(&[&arg], format!("_basic({arg})"))));
+            },
+            SecuritySchemeType::ApiKey { where_in, name } => {
+                args.push((arg.clone(), annotation("str")));
+
+                match where_in {
+                    ApiKeyIn::Header => headers.push(format!("{}: {arg}", to_python_string(name))),
+                    ApiKeyIn::Query => query.push(format!("{}: {arg}", to_python_string(name))),
+                    ApiKeyIn::Cookie => cookies.push((name.clone(), arg)),
+                }
+            },
+        }
+    }
+
+    if !cookies.is_empty() {
+        let value = cookies.iter().map(|(name, arg)| format!("{name}={{{arg}}}")).collect::<Vec<String>>().join("; ");
+        let cookie_args = cookies.iter().map(|(_, arg)| arg).collect::<Vec<&String>>();
+        headers.push(format!("'Cookie': {}", when_given(&cookie_args, format!("f'{value}'"))));
+    }
+
+    AuthPython { args, headers, query }
+}
+
+/*
+    /lottery/draws/{universe} -> f'/lottery/draws/{_path_value(params.universe)}'
+*/
+fn generate_url(url: &str) -> String {
+    if !url.contains('{') {
+        return to_python_string(url);
+    }
+
+    let url = url
+        .split('/')
+        .map(|chunk| match chunk.starts_with('{') && chunk.ends_with('}') {
+            true => {
+                let field = to_snake_case(&chunk[1..chunk.len() - 1]);
+                format!("{{_path_value(params.{field})}}")
+            },
+            false => chunk.replace('{', "{{").replace('}', "}}").replace('\'', "\\'"),
+        })
+        .collect::<Vec<String>>()
+        .join("/");
+
+    format!("f'{url}'")
+}
+
+#[test]
+fn test_generate_url() {
+    assert_eq!(generate_url("/lottery/draws/{universe-id}"), "f'/lottery/draws/{_path_value(params.universe_id)}'");
+    assert_eq!(generate_url("/public/info"), "'/public/info'");
+}
+
+fn generate_operation(types: &mut PythonTypes, method_name: &str, url: &str, method: &OpenApiMethod, handler: &SpecHandlerType) -> String {
+    let left = '{';
+    let right = '}';
+
+    let name_class = to_type_name(method_name);
+    let name_fn = to_snake_case(method_name);
+
+    let mut fields = HashSet::new();
+    let mut params_lines = Vec::<String>::new();
+    let mut query = Vec::<String>::new();
+    let mut headers = Vec::<String>::new();
+    let mut body = None;
+
+    for param in handler.parameters.iter() {
+        let field = unique(&mut fields, to_snake_case(&param.name));
+        let type_python = types.get_type(&format!("{name_class}Params{}", to_type_name(&param.name)), &param.api_type);
+        let default = if type_python.starts_with("Optional[") { " = None" } else { "" };
+        params_lines.push(format!("    {field}: {type_python}{default}"));
+
+        let name = to_python_string(&param.name);
+        match param.where_in {
+            ParamIn::Path => {},
+            ParamIn::Query => query.push(format!("{name}: params.{field}")),
+            ParamIn::Header => headers.push(format!("{name}: params.{field}")),
+            ParamIn::Body => body = Some(format!("_jsonable(params.{field})")),
+        }
+    }
+
+    //the fields without a default go first
+    params_lines.sort_by_key(|line| line.ends_with(" = None"));
+
+    if params_lines.is_empty() {
+        params_lines.push("    pass".into());
+    }
+
+    let name_params = types.new_name(&format!("{name_class}Params"));
+    let params_lines = params_lines.join("\n");
+    types.add_def(format!("class {name_params}(BaseModel):\n{params_lines}"));
+
+    let mut statuses = Vec::<String>::new();
+    let mut ifs = Vec::<String>::new();
+
+    for (code, response) in handler.responses.get_sorted() {
+        let type_python = types.add_named(&format!("{name_class}Response{code}"), response);
+        let name_status = types.new_name(&format!("{name_class}ResponseStatus{code}"));
+
+        types.add_def(format!("class {name_status}(BaseModel):\n    status: Literal[{code}] = {code}\n    body: {type_python}"));
+        statuses.push(name_status.clone());
+        ifs.push(format!("    if response.status_code == {code}:\n        return {name_status}(body=_decode({type_python}, response))"));
+    }
+
+    let name_response = types.new_name(&format!("{name_class}Response"));
+    let response_union = match statuses.is_empty() {
+        true => "Any".to_string(),
+        false => format!("Union[{}]", statuses.join(", ")),
+    };
+    types.add_def(format!("{name_response} = {response_union}"));
+
+    let auth = generate_auth(handler);
+    query.extend(auth.query);
+    headers.extend(auth.headers);
+
+    let mut request_args = vec!(format!("'{}'", method.to_upper_case()), generate_url(url));
+
+    if !query.is_empty() {
+        let query = query.join(", ");
+        request_args.push(format!("params=_query({left}{query}{right})"));
+    }
+
+    if !headers.is_empty() {
+        let headers = headers.join(", ");
+        request_args.push(format!("headers=_headers({left}{headers}{right})"));
+    }
+
+    if let Some(body) = body {
+        request_args.push(format!("json={body}"));
+    }
+
+    let request_args = request_args.into_iter().map(|arg| format!("        {arg},")).collect::<Vec<String>>().join("\n");
+    let auth_decl = auth.args.iter().map(|(name, annotation)| format!(", {name}: {annotation}")).collect::<String>();
+    let auth_kwargs = match auth.args.is_empty() {
+        true => String::new(),
+        false => format!(", *{auth_decl}"),
+    };
+    let auth_call = auth.args.iter().map(|(name, _)| format!(", {name}")).collect::<String>();
+    let ifs = ifs.join("\n");
+    let method_upper = method.to_upper_case();
+
+    format!(r#"def _{name_fn}_request(client: Union[httpx.Client, httpx.AsyncClient], params: {name_params}{auth_decl}) -> httpx.Request:
+    return client.build_request(
+{request_args}
+    )
+
+
+def _{name_fn}_response(response: httpx.Response) -> {name_response}:
+{ifs}
+    raise OpenapiError(response)
+
+
+def {name_fn}(client: httpx.Client, params: {name_params}{auth_kwargs}) -> {name_response}:
+    '''{method_upper} {url}'''
+    return _{name_fn}_response(client.send(_{name_fn}_request(client, params{auth_call})))
+
+
+async def {name_fn}_async(client: httpx.AsyncClient, params: {name_params}{auth_kwargs}) -> {name_response}:
+    '''{method_upper} {url}'''
+    return _{name_fn}_response(await client.send(_{name_fn}_request(client, params{auth_call})))"#)
+}
+
+/*
+    One file per prefix: openapi_{prefix}.py with pydantic v2 models and a sync and an async httpx function per operation.
+    The functions take a client created with base_url, the paths of the operations are relative.
+*/
+pub struct PythonClientGenerator;
+
+impl Generator for PythonClientGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { prefix, spec, methods, .. } = input;
+
+        let mut methods = methods.iter().collect::<Vec<_>>();
+        methods.sort_by_key(|(method_name, _)| *method_name);
+
+        let mut types = PythonTypes::new();
+        let mut names = Vec::<String>::new();
+        let mut functions = Vec::<String>::new();
+
+        for (method_name, OperationRef { url, method }) in methods {
+            let name_fn = to_snake_case(method_name);
+            if names.contains(&name_fn) {
+                return Err(ErrorProcess::message(format!("{prefix}: two methods give the same function name {name_fn}")));
+            }
+            names.push(name_fn);
+
+            let handler = spec.get_handler(url, method)?;
+            functions.push(generate_operation(&mut types, method_name, url, method, handler));
+        }
+
+        let out = [
+            "#The contents of this file have been generated automatically. Do not edit this file.".to_string(),
+            CLIENT_RUNTIME.to_string(),
+            types.get_defs(),
+            functions.join("\n\n\n"),
+        ];
+
+        Ok(vec!(RenderedFile {
+            file_name: format!("openapi_{prefix}.py"),
+            content: format!("{}\n", out.join("\n\n\n")),
+        }))
+    }
+}
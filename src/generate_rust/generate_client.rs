@@ -0,0 +1,313 @@
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{ApiKeyIn, OpenApiMethod, OperationRef, ParamIn, SecuritySchemeType, SpecHandlerType};
+use crate::output::RenderedFile;
+use crate::utils::ErrorProcess;
+use super::generate_types::{ParamRust, RustTypes, generate_params_struct};
+use super::{indent, to_snake_case};
+
+/*
+    Shared by the modules of the operations, they refer to it with super::
+*/
+const CLIENT_RUNTIME: &str = r#"#[derive(Debug)]
+pub enum OpenapiError {
+    Http(reqwest::Error),
+    Decode { status: u16, error: serde_json::Error },
+    UnexpectedStatus { status: u16, body: String },
+}
+
+impl std::fmt::Display for OpenapiError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(err) => write!(fmt, "{err}"),
+            Self::Decode { status, error } => write!(fmt, "Response decoding error, status {status} -> {error}"),
+            Self::UnexpectedStatus { status, .. } => write!(fmt, "Unhandled response, status {status}"),
+        }
+    }
+}
+
+impl std::error::Error for OpenapiError {}
+
+impl From<reqwest::Error> for OpenapiError {
+    fn from(err: reqwest::Error) -> Self {
+        OpenapiError::Http(err)
+    }
+}
+
+fn decode<T: serde::de::DeserializeOwned>(status: u16, body: &str) -> Result<T, OpenapiError> {
+    let body = if body.is_empty() { "null" } else { body };
+    serde_json::from_str(body).map_err(|error| OpenapiError::Decode { status, error })
+}
+
+/*
+    The parameters are sent as text, null is skipped and an array is sent as a repeated parameter
+*/
+fn to_text<T: serde::Serialize>(value: &T) -> Vec<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Null) | Err(_) => Vec::new(),
+        Ok(serde_json::Value::String(value)) => vec!(value),
+        Ok(serde_json::Value::Array(list)) => list.iter().flat_map(to_text).collect(),
+        Ok(value) => vec!(value.to_string()),
+    }
+}
+
+#[allow(dead_code)]
+fn path_value<T: serde::Serialize>(value: &T) -> String {
+    to_text(value).join(",")
+}
+
+#[allow(dead_code)]
+fn add_query<T: serde::Serialize>(query: &mut Vec<(&'static str, String)>, name: &'static str, value: &T) {
+    for value in to_text(value) {
+        query.push((name, value));
+    }
+}
+
+#[allow(dead_code)]
+fn add_header<T: serde::Serialize>(request: reqwest::RequestBuilder, name: &'static str, value: &T) -> reqwest::RequestBuilder {
+    match to_text(value) {
+        list if list.is_empty() => request,
+        list => request.header(name, list.join(",")),
+    }
+}"#;
+
+struct AuthRust {
+    args_decl: String,                  //", backend_token: &str"
+    request: Vec<String>,               //lines changing the request
+    query: Vec<String>,                 //lines adding to the query
+}
+
+/*
+    With the optional authentication the arguments are Option<...>, a credential is sent only when it is given:
+    let request = match api_key { Some(api_key) => request.header("X-Api-Key", api_key), None => request };
+*/
+fn generate_auth(handler: &SpecHandlerType) -> AuthRust {
+    let optional = handler.security_optional;
+
+    let wrap_type = |type_rust: &str| match optional {
+        true => format!("Option<{type_rust}>"),
+        false => type_rust.to_string(),
+    };
+
+    let wrap_request = |args: &[&String], request: String| match (optional, args) {
+        (false, _) => format!("let request = {request};"),
+        (true, [arg]) => format!("let request = match {arg} {{ Some({arg}) => {request}, None => request }};"),
+        (true, args) => {
+            let values = args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().join(", ");
+            let patterns = args.iter().map(|arg| format!("Some({arg})")).collect::<Vec<String>>().join(", ");
+            format!("let request = match ({values}) {{ ({patterns}) => {request}, _ => request }};")
+        },
+    };
+
+    let mut args_decl = Vec::<String>::new();
+    let mut request = Vec::<String>::new();
+    let mut query = Vec::<String>::new();
+    let mut cookies = Vec::<(String, String)>::new();
+
+    for security in handler.security.iter() {
+        let arg = to_snake_case(&security.name);
+
+        match &security.scheme {
+            SecuritySchemeType::Bearer => {
+                if args_decl.iter().any(|arg| arg.starts_with("backend_token:")) {
+                    continue;
+                }
+
+                let backend_token = "backend_token".to_string();
+                args_decl.push(format!("backend_token: {}", wrap_type("&str")));
+                request.push(wrap_request(&[&backend_token], "request.bearer_auth(backend_token)".into()));
+            },
+            SecuritySchemeType::Basic => {
+                args_decl.push(format!("{arg}: {}", wrap_type("(&str, &str)")));
+                request.push(wrap_request(&[&arg], format!("request.basic_auth({arg}.0, Some({arg}.1))")));
+            },
+            SecuritySchemeType::ApiKey { where_in, name } => {
+                args_decl.push(format!("{arg}: {}", wrap_type("&str")));
+
+                match where_in {
+                    ApiKeyIn::Header => request.push(wrap_request(&[&arg], format!("request.header({name:?}, {arg})"))),
+                    ApiKeyIn::Query => match optional {
+                        true => query.push(format!("if let Some({arg}) = {arg} {{ query.push(({name:?}, {arg}.to_string())); }}")),
+                        false => query.push(format!("query.push(({name:?}, {arg}.to_string()));")),
+                    },
+                    ApiKeyIn::Cookie => cookies.push((name.clone(), arg)),
+                }
+            },
+        }
+    }
+
+    if !cookies.is_empty() {
+        let format = cookies.iter().map(|(name, _)| format!("{name}={{}}")).collect::<Vec<String>>().join("; ");
+        let args = cookies.iter().map(|(_, arg)| arg).collect::<Vec<&String>>();
+        let values = args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().join(", ");
+        request.push(wrap_request(&args, format!("request.header(\"Cookie\", format!({format:?}, {values}))")));
+    }
+
+    let args_decl = args_decl.into_iter().map(|arg| format!(", {arg}")).collect::<String>();
+
+    AuthRust { args_decl, request, query }
+}
+
+/*
+    /lottery/draws/{universe} -> format!("{api_url}/lottery/draws/{}", super::path_value(&params.universe))
+*/
+fn generate_url(url: &str) -> String {
+    let mut format = String::from("{api_url}");
+    let mut args = Vec::<String>::new();
+
+    for chunk in url.split('/').skip(1) {
+        format.push('/');
+
+        if chunk.starts_with('{') && chunk.ends_with('}') {
+            let field = to_snake_case(&chunk[1..chunk.len() - 1]);
+            format.push_str("{}");
+            args.push(format!(", super::path_value(&params.{field})"));
+        } else {
+            format.push_str(&chunk.replace('{', "{{").replace('}', "}}"));
+        }
+    }
+
+    let args = args.concat();
+    format!("format!({format:?}{args})")
+}
+
+#[test]
+fn test_generate_url() {
+    assert_eq!(
+        generate_url("/lottery/draws/{universe-id}"),
+        "format!(\"{api_url}/lottery/draws/{}\", super::path_value(&params.universe_id))"
+    );
+}
+
+fn get_reqwest_method(method: &OpenApiMethod) -> String {
+    let method = method.to_upper_case();
+    format!("reqwest::Method::{method}")
+}
+
+/*
+    pub mod get_current_draw { Params, Response200, Response, request() }
+*/
+fn generate_module(method_name: &str, url: &str, method: &OpenApiMethod, handler: &SpecHandlerType) -> String {
+    let left = '{';
+    let right = '}';
+
+    let mut types = RustTypes::new();
+    let params = types.add_params(handler);
+    let responses = types.add_responses(handler);
+
+    let mut query = Vec::<String>::new();
+    let mut headers = Vec::<String>::new();
+    let mut body = None;
+
+    for ParamRust { param, field, .. } in params.iter() {
+        let name = &param.name;
+        match param.where_in {
+            ParamIn::Path => {},
+            ParamIn::Query => query.push(format!("super::add_query(&mut query, {name:?}, &params.{field});")),
+            ParamIn::Header => headers.push(format!("let request = super::add_header(request, {name:?}, &params.{field});")),
+            ParamIn::Body => body = Some(format!("let request = request.json(&params.{field});")),
+        }
+    }
+
+    let params_struct = generate_params_struct("Params", &params.iter().collect::<Vec<_>>(), false);
+
+    let mut variants = Vec::<String>::new();
+    let mut arms = Vec::<String>::new();
+
+    for (code, type_rust) in responses {
+        variants.push(format!("    Status{code}({type_rust}),"));
+        arms.push(format!("        {code} => Ok(Response::Status{code}(super::decode(status, &body)?)),"));
+    }
+
+    let auth = generate_auth(handler);
+    let auth_args_decl = auth.args_decl;
+    query.extend(auth.query);
+
+    let mut request_lines = vec!(format!("let request = client.request({}, url);", get_reqwest_method(method)));
+
+    if !query.is_empty() {
+        request_lines.insert(0, "let mut query = Vec::<(&str, String)>::new();".into());
+        request_lines.splice(1..1, query);
+        request_lines.push("let request = request.query(&query);".into());
+    }
+
+    request_lines.extend(headers);
+    request_lines.extend(auth.request);
+    request_lines.extend(body);
+
+    let request_lines = request_lines.into_iter().map(|line| format!("    {line}")).collect::<Vec<String>>().join("\n");
+    let defs = types.get_defs();
+    let variants = variants.join("\n");
+    let arms = arms.join("\n");
+    let generate_url = generate_url(url);
+    let method_upper = method.to_upper_case();
+    let params_name = if handler.parameters.is_empty() { "_params" } else { "params" };
+
+    let content = format!(r#"use serde::{left}Deserialize, Serialize{right};
+
+{params_struct}
+
+{defs}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {left}
+{variants}
+{right}
+
+/// {method_upper} {url}
+pub async fn request(client: &reqwest::Client, api_url: &str{auth_args_decl}, {params_name}: &Params) -> Result<Response, super::OpenapiError> {left}
+    let url = {generate_url};
+{request_lines}
+
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.text().await?;
+
+    match status {left}
+{arms}
+        _ => Err(super::OpenapiError::UnexpectedStatus {left} status, body {right}),
+    {right}
+{right}"#);
+
+    let content = indent(&content.replace("\n\n\n\n", "\n\n"), 4);
+    let module = to_snake_case(method_name);
+
+    format!("pub mod {module} {left}\n{content}\n{right}")
+}
+
+/*
+    One file per prefix: openapi_{prefix}.rs, with a module per operation.
+    The generated code needs serde (derive), serde_json and reqwest (json) in the dependencies.
+*/
+pub struct RustClientGenerator;
+
+impl Generator for RustClientGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { prefix, spec, methods, .. } = input;
+
+        let mut methods = methods.iter().collect::<Vec<_>>();
+        methods.sort_by_key(|(method_name, _)| *method_name);
+
+        let mut out = vec!(
+            "//The contents of this file have been generated automatically. Do not edit this file.".to_string(),
+            CLIENT_RUNTIME.to_string(),
+        );
+
+        let mut modules = Vec::new();
+
+        for (method_name, OperationRef { url, method }) in methods {
+            let module = to_snake_case(method_name);
+            if modules.contains(&module) {
+                return Err(ErrorProcess::message(format!("{prefix}: two methods give the same module name {module}")));
+            }
+            modules.push(module);
+
+            let handler = spec.get_handler(url, method)?;
+            out.push(generate_module(method_name, url, method, handler));
+        }
+
+        Ok(vec!(RenderedFile {
+            file_name: format!("openapi_{prefix}.rs"),
+            content: format!("{}\n", out.join("\n\n")),
+        }))
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use crate::generator::{Generator, GeneratorInput};
+use crate::open_api_spec::{OpenApiMethod, OperationRef, ParamIn, SpecHandlerType};
+use crate::output::RenderedFile;
+use crate::utils::ErrorProcess;
+use super::generate_types::{ParamRust, RustTypes, generate_params_struct};
+use super::{indent, to_snake_case};
+
+/*
+    Shared by the modules of the operations, they refer to it with super::
+*/
+const SERVER_RUNTIME: &str = r#"/*
+    The value of a header parameter, a missing header is decoded as null.
+    The text is decoded as a string first and then as json, for the numbers and the booleans.
+*/
+#[allow(dead_code)]
+fn header_value<T: serde::de::DeserializeOwned>(headers: &axum::http::HeaderMap, name: &str) -> Result<T, (axum::http::StatusCode, String)> {
+    let value = match headers.get(name).map(|value| value.to_str()) {
+        None => serde_json::Value::Null,
+        Some(Ok(value)) => serde_json::Value::String(value.to_string()),
+        Some(Err(err)) => {
+            return Err((axum::http::StatusCode::BAD_REQUEST, format!("header {name} -> {err}")));
+        }
+    };
+
+    if let Ok(out) = serde_json::from_value::<T>(value.clone()) {
+        return Ok(out);
+    }
+
+    let text = value.as_str().unwrap_or("null");
+    serde_json::from_str::<T>(text).map_err(|err| {
+        (axum::http::StatusCode::BAD_REQUEST, format!("header {name} -> {err}"))
+    })
+}
+
+#[allow(dead_code)]
+fn into_response<T: serde::Serialize>(status: u16, body: T) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    let status = axum::http::StatusCode::from_u16(status).unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    (status, axum::Json(body)).into_response()
+}"#;
+
+/*
+    /lottery/draws/{universe-id} -> /lottery/draws/:universe-id
+*/
+fn get_axum_path(url: &str) -> String {
+    url.split('/')
+        .map(|chunk| match chunk.starts_with('{') && chunk.ends_with('}') {
+            true => format!(":{}", &chunk[1..chunk.len() - 1]),
+            false => chunk.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+#[test]
+fn test_get_axum_path() {
+    assert_eq!(get_axum_path("/lottery/draws/{universe-id}"), "/lottery/draws/:universe-id");
+    assert_eq!(get_axum_path("/public/info"), "/public/info");
+}
+
+/*
+    get(a) for the first operation of the path, .put(b) for the next ones
+*/
+fn get_method_router(handlers: &[(&OpenApiMethod, String)]) -> String {
+    handlers
+        .iter()
+        .enumerate()
+        .map(|(index, (method, handler))| match index {
+            0 => format!("axum::routing::{method}({handler})"),
+            _ => format!(".{method}({handler})"),
+        })
+        .collect::<String>()
+}
+
+/*
+    pub mod get_current_draw { Params, PathParams, QueryParams, Response200, Response, handler() }
+*/
+fn generate_module(module: &str, handler: &SpecHandlerType) -> String {
+    let left = '{';
+    let right = '}';
+
+    let mut types = RustTypes::new();
+    let params = types.add_params(handler);
+    let responses = types.add_responses(handler);
+
+    let get_params = |where_in: ParamIn| params.iter().filter(|param| param.param.where_in == where_in).collect::<Vec<_>>();
+    let path_params = get_params(ParamIn::Path);
+    let query_params = get_params(ParamIn::Query);
+    let header_params = get_params(ParamIn::Header);
+    let body_param = get_params(ParamIn::Body).into_iter().next();
+
+    let mut structs = vec!(generate_params_struct("Params", &params.iter().collect::<Vec<_>>(), false));
+    let mut extractors = vec!("axum::extract::State(api): axum::extract::State<T>".to_string());
+
+    if !path_params.is_empty() {
+        structs.push(generate_params_struct("PathParams", &path_params, true));
+        extractors.push("axum::extract::Path(path): axum::extract::Path<PathParams>".into());
+    }
+
+    if !query_params.is_empty() {
+        structs.push(generate_params_struct("QueryParams", &query_params, true));
+        extractors.push("axum::extract::Query(query): axum::extract::Query<QueryParams>".into());
+    }
+
+    if !header_params.is_empty() {
+        extractors.push("headers: axum::http::HeaderMap".into());
+    }
+
+    //the body has to be the last extractor
+    if let Some(ParamRust { type_rust, .. }) = body_param {
+        extractors.push(format!("axum::Json(body): axum::Json<{type_rust}>"));
+    }
+
+    let fields = params
+        .iter()
+        .map(|ParamRust { param, field, .. }| {
+            let name = &param.name;
+            let value = match param.where_in {
+                ParamIn::Path => format!("path.{field}"),
+                ParamIn::Query => format!("query.{field}"),
+                ParamIn::Header => format!("super::header_value(&headers, {name:?})?"),
+                ParamIn::Body => "body".into(),
+            };
+            format!("        {field}: {value},")
+        })
+        .collect::<Vec<String>>();
+
+    let params_init = match fields.is_empty() {
+        true => "Params {}".to_string(),
+        false => format!("Params {left}\n{}\n    {right}", fields.join("\n")),
+    };
+
+    let variants = responses
+        .iter()
+        .map(|(code, type_rust)| format!("    Status{code}({type_rust}),"))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let arms = responses
+        .iter()
+        .map(|(code, _)| format!("            Response::Status{code}(body) => super::into_response({code}, body),"))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let match_self = match responses.is_empty() {
+        true => "match self {}".to_string(),
+        false => format!("match self {left}\n{arms}\n        {right}"),
+    };
+
+    let structs = structs.join("\n\n");
+    let defs = types.get_defs();
+    let extractors = extractors.into_iter().map(|extractor| format!("    {extractor},")).collect::<Vec<String>>().join("\n");
+
+    let content = format!(r#"use serde::{left}Deserialize, Serialize{right};
+
+{structs}
+
+{defs}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {left}
+{variants}
+{right}
+
+impl axum::response::IntoResponse for Response {left}
+    fn into_response(self) -> axum::response::Response {left}
+        {match_self}
+    {right}
+{right}
+
+pub async fn handler<T: super::Api>(
+{extractors}
+) -> Result<Response, (axum::http::StatusCode, String)> {left}
+    let params = {params_init};
+
+    Ok(api.{module}(params).await)
+{right}"#);
+
+    let content = indent(&content.replace("\n\n\n\n", "\n\n"), 4);
+
+    format!("pub mod {module} {left}\n{content}\n{right}")
+}
+
+/*
+    One file per prefix: openapi_{prefix}.rs, with a module per operation, the trait Api and router().
+    The handlers are written for axum 0.7, the generated code needs axum, serde (derive) and serde_json.
+    The authentication is not generated, it belongs in a layer of the router.
+*/
+pub struct RustServerGenerator;
+
+impl Generator for RustServerGenerator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+        let GeneratorInput { prefix, spec, methods, .. } = input;
+
+        let mut methods = methods.iter().collect::<Vec<_>>();
+        methods.sort_by_key(|(method_name, _)| *method_name);
+
+        let mut names = Vec::<String>::new();
+        let mut modules = Vec::<String>::new();
+        let mut trait_methods = Vec::<String>::new();
+        let mut routes = BTreeMap::<String, Vec<(&OpenApiMethod, String)>>::new();
+
+        for (method_name, OperationRef { url, method }) in methods {
+            let module = to_snake_case(method_name);
+            if names.contains(&module) {
+                return Err(ErrorProcess::message(format!("{prefix}: two methods give the same module name {module}")));
+            }
+            names.push(module.clone());
+
+            let handler = spec.get_handler(url, method)?;
+
+            let method_upper = method.to_upper_case();
+            trait_methods.push(format!("    /// {method_upper} {url}"));
+            trait_methods.push(format!(
+                "    fn {module}(&self, params: {module}::Params) -> impl std::future::Future<Output = {module}::Response> + Send;"
+            ));
+
+            routes.entry(get_axum_path(url)).or_default().push((method, format!("{module}::handler::<T>")));
+
+            modules.push(generate_module(&module, handler));
+        }
+
+        let trait_methods = trait_methods.join("\n");
+
+        let routes = routes
+            .into_iter()
+            .map(|(path, handlers)| {
+                let handlers = get_method_router(&handlers);
+                format!("        .route({path:?}, {handlers})")
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let api = format!(r#"/*
+    One method per operation, the implementation is shared by the handlers as the state of the router
+*/
+pub trait Api: Clone + Send + Sync + 'static {{
+{trait_methods}
+}}
+
+pub fn router<T: Api>(api: T) -> axum::Router {{
+    axum::Router::new()
+{routes}
+        .with_state(api)
+}}"#);
+
+        let mut out = vec!(
+            "//The contents of this file have been generated automatically. Do not edit this file.".to_string(),
+            SERVER_RUNTIME.to_string(),
+            api,
+        );
+
+        out.extend(modules);
+
+        Ok(vec!(RenderedFile {
+            file_name: format!("openapi_{prefix}.rs"),
+            content: format!("{}\n", out.join("\n\n")),
+        }))
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+
+use crate::open_api_spec::{ParametersType, SpecHandlerType};
+use crate::open_api_type::OpenApiType;
+use super::{to_snake_case, to_type_name};
+
+const DERIVE: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+
+/*
+    Collects the declarations of the named types of one module.
+    Objects become structs, unions become enums, the nested types are named after their path:
+    Response200 { items: [{ ... }] } -> Response200, Response200Items, Response200ItemsItem
+*/
+pub struct RustTypes {
+    defs: Vec<String>,
+    names: HashSet<String>,
+}
+
+pub struct ParamRust<'a> {
+    pub param: &'a ParametersType,
+    pub field: String,                                  //universe-id -> universe_id
+    pub type_rust: String,
+}
+
+/*
+    A struct with a field per parameter, with `rename` the fields keep the names of the parameters when (de)serialized
+*/
+pub fn generate_params_struct(name: &str, params: &[&ParamRust], rename: bool) -> String {
+    let mut out = vec!(DERIVE.to_string(), format!("pub struct {name} {{"));
+
+    for ParamRust { param, field, type_rust } in params {
+        let key = &param.name;
+
+        if rename && field.trim_start_matches("r#") != key {
+            out.push(format!("    #[serde(rename = {key:?})]"));
+        }
+
+        out.push(format!("    pub {field}: {type_rust},"));
+    }
+
+    if params.is_empty() {
+        return format!("{DERIVE}\npub struct {name} {{}}");
+    }
+
+    out.push("}".into());
+    out.join("\n")
+}
+
+fn wrap_option(required: bool, type_rust: String) -> String {
+    match required {
+        true => type_rust,
+        false => format!("Option<{type_rust}>"),
+    }
+}
+
+fn get_variant_name(type_param: &OpenApiType) -> String {
+    match type_param {
+        OpenApiType::LiteralString { value, .. } => to_type_name(value),
+        OpenApiType::String { .. } => "String".into(),
+        OpenApiType::Number { .. } => "Number".into(),
+        OpenApiType::Boolean { .. } => "Boolean".into(),
+        OpenApiType::Array { .. } => "Array".into(),
+        OpenApiType::Object { .. } => "Object".into(),
+        OpenApiType::Record { .. } => "Record".into(),
+        OpenApiType::Union { .. } => "Union".into(),
+        OpenApiType::Unknown => "Unknown".into(),
+    }
+}
+
+fn unique(used: &mut HashSet<String>, name: String) -> String {
+    let mut out = name.clone();
+    let mut index = 2;
+
+    while used.contains(&out) {
+        out = format!("{name}{index}");
+        index += 1;
+    }
+
+    used.insert(out.clone());
+    out
+}
+
+impl RustTypes {
+    pub fn new() -> RustTypes {
+        RustTypes {
+            defs: Vec::new(),
+            names: HashSet::new(),
+        }
+    }
+
+    /*
+        The types of the parameters, named Params{Name}
+    */
+    pub fn add_params<'a>(&mut self, handler: &'a SpecHandlerType) -> Vec<ParamRust<'a>> {
+        let mut fields = HashSet::new();
+
+        handler.parameters
+            .iter()
+            .map(|param| ParamRust {
+                param,
+                field: unique(&mut fields, to_snake_case(&param.name)),
+                type_rust: self.get_type(&format!("Params{}", to_type_name(&param.name)), &param.api_type),
+            })
+            .collect()
+    }
+
+    /*
+        status -> the type of the body, named Response{status}
+    */
+    pub fn add_responses(&mut self, handler: &SpecHandlerType) -> Vec<(u16, String)> {
+        handler.responses
+            .get_sorted()
+            .into_iter()
+            .map(|(code, response)| (*code, self.add_named(&format!("Response{code}"), response)))
+            .collect()
+    }
+
+    pub fn get_defs(&self) -> String {
+        self.defs.join("\n\n")
+    }
+
+    fn new_name(&mut self, name: &str) -> String {
+        unique(&mut self.names, name.to_string())
+    }
+
+    /*
+        Declares `pub type {name} = ...` when the type is not a struct or an enum with that name.
+    */
+    pub fn add_named(&mut self, name: &str, type_param: &OpenApiType) -> String {
+        let type_rust = self.get_type(name, type_param);
+
+        if type_rust != name {
+            let name = self.new_name(name);
+            self.defs.push(format!("pub type {name} = {type_rust};"));
+            return name;
+        }
+
+        type_rust
+    }
+
+    /*
+        The type of a value, with Option<...> when the value is not required.
+        The structs and enums are declared on the way, `name` is a suggestion for their name.
+    */
+    pub fn get_type(&mut self, name: &str, type_param: &OpenApiType) -> String {
+        match type_param {
+            OpenApiType::LiteralString { required, value } => {
+                let type_rust = self.add_literal_enum(name, &[value]);
+                wrap_option(*required, type_rust)
+            },
+            OpenApiType::String { required } => wrap_option(*required, "String".into()),
+            OpenApiType::Number { required } => wrap_option(*required, "f64".into()),
+            OpenApiType::Boolean { required } => wrap_option(*required, "bool".into()),
+            OpenApiType::Unknown => "serde_json::Value".into(),
+            OpenApiType::Array { required, items } => {
+                let items = self.get_type(&format!("{name}Item"), items);
+                wrap_option(*required, format!("Vec<{items}>"))
+            },
+            OpenApiType::Record { required, value } => {
+                let value = self.get_type(&format!("{name}Value"), value);
+                wrap_option(*required, format!("std::collections::BTreeMap<String, {value}>"))
+            },
+            OpenApiType::Object { required, props } => {
+                let type_rust = self.add_struct(name, props.get_sorted());
+                wrap_option(*required, type_rust)
+            },
+            OpenApiType::Union { required, list } => {
+                let type_rust = self.add_union(name, list);
+                wrap_option(*required, type_rust)
+            },
+        }
+    }
+
+    fn add_struct(&mut self, name: &str, props: Vec<(&String, &OpenApiType)>) -> String {
+        let name = self.new_name(name);
+        let mut fields = HashSet::new();
+        let mut out = vec!(DERIVE.to_string(), format!("pub struct {name} {{"));
+
+        for (key, value) in props {
+            let field = unique(&mut fields, to_snake_case(key));
+            let type_rust = self.get_type(&format!("{name}{}", to_type_name(key)), value);
+
+            if field.trim_start_matches("r#") != key {
+                out.push(format!("    #[serde(rename = {key:?})]"));
+            }
+
+            if !value.is_required() {
+                out.push("    #[serde(default, skip_serializing_if = \"Option::is_none\")]".into());
+            }
+
+            out.push(format!("    pub {field}: {type_rust},"));
+        }
+
+        out.push("}".into());
+        self.defs.push(out.join("\n"));
+        name
+    }
+
+    fn add_literal_enum(&mut self, name: &str, values: &[&String]) -> String {
+        let name = self.new_name(name);
+        let mut variants = HashSet::new();
+        let mut out = vec!(DERIVE.to_string(), format!("pub enum {name} {{"));
+
+        for value in values {
+            let variant = unique(&mut variants, to_type_name(value));
+            out.push(format!("    #[serde(rename = {value:?})]"));
+            out.push(format!("    {variant},"));
+        }
+
+        out.push("}".into());
+        self.defs.push(out.join("\n"));
+        name
+    }
+
+    /*
+        'a' | 'b'         -> enum with renamed variants
+        string | { ... }  -> #[serde(untagged)] enum, the variants are tried in order
+    */
+    fn add_union(&mut self, name: &str, list: &[OpenApiType]) -> String {
+        let literals = list
+            .iter()
+            .map(|item| match item {
+                OpenApiType::LiteralString { value, required: true } => Some(value),
+                _ => None,
+            })
+            .collect::<Option<Vec<&String>>>();
+
+        match (list, literals) {
+            ([], _) => "serde_json::Value".into(),
+            ([item], _) => self.get_type(name, item),
+            (_, Some(literals)) => self.add_literal_enum(name, &literals),
+            (list, None) => {
+                let name = self.new_name(name);
+                let mut variants = HashSet::new();
+                let mut out = vec!(DERIVE.to_string(), "#[serde(untagged)]".into(), format!("pub enum {name} {{"));
+
+                for item in list {
+                    let variant = unique(&mut variants, get_variant_name(item));
+                    let type_rust = self.get_type(&format!("{name}{variant}"), item);
+                    out.push(format!("    {variant}({type_rust}),"));
+                }
+
+                out.push("}".into());
+                self.defs.push(out.join("\n"));
+                name
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rust_types_union() {
+    let mut types = RustTypes::new();
+
+    let type_param = OpenApiType::Union {
+        required: false,
+        list: vec!(
+            OpenApiType::LiteralString { required: true, value: "pl".into() },
+            OpenApiType::LiteralString { required: true, value: "en-gb".into() },
+        ),
+    };
+
+    assert_eq!(types.get_type("Lang", &type_param), "Option<Lang>");
+    assert_eq!(types.get_defs(), [
+        DERIVE,
+        "pub enum Lang {",
+        "    #[serde(rename = \"pl\")]",
+        "    Pl,",
+        "    #[serde(rename = \"en-gb\")]",
+        "    EnGb,",
+        "}",
+    ].join("\n"));
+}
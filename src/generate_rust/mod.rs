@@ -0,0 +1,131 @@
+mod generate_types;
+mod generate_client;
+mod generate_server;
+
+pub use generate_client::RustClientGenerator;
+pub use generate_server::RustServerGenerator;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen",
+    "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/*
+    Splits a name on everything which is not alphanumeric and on the case changes:
+    getCurrentDraw -> [get, Current, Draw], universe-id -> [universe, id], HTTPCode -> [HTTP, Code]
+*/
+pub fn split_words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<char>>();
+    let mut out = Vec::<String>::new();
+    let mut word = String::new();
+
+    for (index, char) in chars.iter().enumerate() {
+        if !char.is_alphanumeric() {
+            if !word.is_empty() {
+                out.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        if char.is_uppercase() && !word.is_empty() {
+            let prev = chars[index - 1];
+            let next_lower = chars.get(index + 1).map(|next| next.is_lowercase()).unwrap_or(false);
+
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                out.push(std::mem::take(&mut word));
+            }
+        }
+
+        word.push(*char);
+    }
+
+    if !word.is_empty() {
+        out.push(word);
+    }
+
+    out
+}
+
+/*
+    A valid identifier for a field, a function or a module: getCurrentDraw -> get_current_draw, type -> r#type
+*/
+pub fn to_snake_case(name: &str) -> String {
+    let words = split_words(name).into_iter().map(|word| word.to_lowercase()).collect::<Vec<String>>();
+    let out = words.join("_");
+
+    if out.is_empty() {
+        return "field".into();
+    }
+
+    if out.starts_with(|char: char| char.is_numeric()) {
+        return format!("_{out}");
+    }
+
+    if KEYWORDS.contains(&out.as_str()) {
+        return format!("r#{out}");
+    }
+
+    if out == "self" || out == "super" || out == "crate" {
+        return format!("{out}_");
+    }
+
+    out
+}
+
+#[test]
+fn test_to_snake_case() {
+    assert_eq!(to_snake_case("getCurrentDraw"), "get_current_draw");
+    assert_eq!(to_snake_case("universe-id"), "universe_id");
+    assert_eq!(to_snake_case("HTTPCode"), "http_code");
+    assert_eq!(to_snake_case("type"), "r#type");
+    assert_eq!(to_snake_case("2fa"), "_2fa");
+}
+
+/*
+    A valid name of a type or of a variant: pending-payment -> PendingPayment, 5 -> V5
+*/
+pub fn to_type_name(name: &str) -> String {
+    let out = split_words(name)
+        .into_iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(|char| char.to_lowercase())).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    if out.is_empty() {
+        return "Empty".into();
+    }
+
+    if out.starts_with(|char: char| char.is_numeric()) {
+        return format!("V{out}");
+    }
+
+    out
+}
+
+#[test]
+fn test_to_type_name() {
+    assert_eq!(to_type_name("pending-payment"), "PendingPayment");
+    assert_eq!(to_type_name("requestBody"), "RequestBody");
+    assert_eq!(to_type_name("5"), "V5");
+    assert_eq!(to_type_name(""), "Empty");
+}
+
+/*
+    Adds an indentation to every non-empty line, used for the content of `pub mod name { ... }`
+*/
+fn indent(content: &str, ident: usize) -> String {
+    let ident = " ".repeat(ident);
+
+    content
+        .lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("{ident}{line}") })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
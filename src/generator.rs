@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use serde_json::Value;
+
+use crate::generate_js::{IoTsGenerator, TemplateGenerator, TypesOnlyGenerator, ZodGenerator};
+use crate::generate_json_schema::JsonSchemaGenerator;
+use crate::generate_python::PythonClientGenerator;
+use crate::generate_rust::{RustClientGenerator, RustServerGenerator};
+use crate::open_api_spec::{OperationRef, SpecOpenApi};
+use crate::output::RenderedFile;
+use crate::read_wanted_spec::GeneratorConfig;
+use crate::utils::ErrorProcess;
+
+pub struct GeneratorInput<'a> {
+    pub dir_spec: &'a String,                               //the user templates are relative to dir_spec
+    pub prefix: &'a String,
+    pub spec: &'a SpecOpenApi,                              //after applying the rewrite rules
+    pub methods: &'a HashMap<String, OperationRef>,         //method name -> the selected operation
+}
+
+/*
+    A backend turns the selected operations of one prefix into files, the names of the files are up to the backend.
+    The backend and its options are chosen per prefix with "generator" in the wanted spec.
+*/
+pub trait Generator {
+    fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess>;
+}
+
+pub type GeneratorFactory = Box<dyn Fn(&Value) -> Result<Box<dyn Generator>, ErrorProcess> + Send + Sync>;
+
+/*
+    The backends added outside of this crate, picked by name in the wanted spec:
+    "generator": { "type": "custom", "name": "kotlin", "options": { "package": "com.example.api" } }
+    The factory gets "options" and can reject them, the error is reported as a configuration problem.
+*/
+#[derive(Default)]
+pub struct GeneratorRegistry {
+    factories: HashMap<String, GeneratorFactory>,
+}
+
+impl GeneratorRegistry {
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(&Value) -> Result<Box<dyn Generator>, ErrorProcess> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+}
+
+pub fn get_generator(config: &GeneratorConfig, registry: &GeneratorRegistry) -> Result<Box<dyn Generator>, ErrorProcess> {
+    Ok(match config {
+        GeneratorConfig::IoTs(options) => Box::new(IoTsGenerator { options: options.clone() }),
+        GeneratorConfig::Zod(options) => Box::new(ZodGenerator { options: options.clone() }),
+        GeneratorConfig::TypesOnly => Box::new(TypesOnlyGenerator),
+        GeneratorConfig::Template(options) => Box::new(TemplateGenerator { options: options.clone() }),
+        GeneratorConfig::RustClient => Box::new(RustClientGenerator),
+        GeneratorConfig::RustServer => Box::new(RustServerGenerator),
+        GeneratorConfig::PythonClient => Box::new(PythonClientGenerator),
+        GeneratorConfig::JsonSchema(options) => Box::new(JsonSchemaGenerator { options: options.clone() }),
+        GeneratorConfig::Custom { name, options } => match registry.factories.get(name) {
+            Some(factory) => factory(options)?,
+            None => {
+                let mut known = registry.factories.keys().cloned().collect::<Vec<String>>();
+                known.sort();
+                let known = known.join(", ");
+
+                return Err(ErrorProcess::message(format!("Unknown custom generator {name}, registered: [{known}]")));
+            }
+        },
+    })
+}
+
+#[test]
+fn test_get_generator_custom() {
+    struct ListGenerator {
+        file_name: String,
+    }
+
+    impl Generator for ListGenerator {
+        fn generate(&self, input: &GeneratorInput) -> Result<Vec<RenderedFile>, ErrorProcess> {
+            let mut names = input.methods.keys().cloned().collect::<Vec<String>>();
+            names.sort();
+
+            Ok(vec!(RenderedFile {
+                file_name: self.file_name.clone(),
+                content: names.join("\n"),
+            }))
+        }
+    }
+
+    let mut registry = GeneratorRegistry::default();
+    registry.register("list", |options: &Value| -> Result<Box<dyn Generator>, ErrorProcess> {
+        match options.get("file_name").and_then(Value::as_str) {
+            Some(file_name) => Ok(Box::new(ListGenerator { file_name: file_name.to_string() })),
+            None => Err(ErrorProcess::message("list: missing file_name".to_string())),
+        }
+    });
+
+    let config = serde_json::from_value::<GeneratorConfig>(serde_json::json!({
+        "type": "custom", "name": "list", "options": { "file_name": "methods.txt" }
+    })).unwrap();
+
+    let spec = SpecOpenApi { paths: HashMap::new(), operations: HashMap::new(), ambiguous_operations: HashMap::new() };
+    let methods = HashMap::from([
+        ("getDraw".to_string(), OperationRef { url: "/draw".into(), method: crate::open_api_spec::OpenApiMethod::Get }),
+    ]);
+    let input = GeneratorInput { dir_spec: &"spec/".to_string(), prefix: &"lottery".to_string(), spec: &spec, methods: &methods };
+
+    let files = get_generator(&config, &registry).unwrap().generate(&input).unwrap();
+    assert_eq!(files[0].file_name, "methods.txt");
+    assert_eq!(files[0].content, "getDraw");
+
+    let config = serde_json::from_value::<GeneratorConfig>(serde_json::json!({ "type": "custom", "name": "list" })).unwrap();
+    assert!(get_generator(&config, &registry).is_err());
+
+    let config = serde_json::from_value::<GeneratorConfig>(serde_json::json!({ "type": "custom", "name": "kotlin" })).unwrap();
+    assert_eq!(get_generator(&config, &registry).err().unwrap().to_string(), "Unknown custom generator kotlin, registered: [list]");
+}
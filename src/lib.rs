@@ -0,0 +1,76 @@
+/*
+    The generator as a library: the same commands as the binary, with the backends from outside of this crate.
+
+    let mut registry = GeneratorRegistry::default();
+    registry.register("kotlin", |options| Ok(Box::new(KotlinGenerator::new(options)?)));
+    openapi_gen::run(std::env::args().collect(), registry).await
+*/
+mod utils;
+pub mod open_api_type;
+pub mod open_api_spec;
+mod parse_spec;
+mod run_gen;
+mod read_wanted_spec;
+mod select_methods;
+mod scaffold;
+mod validate;
+mod generate_js;
+mod generate_rust;
+mod generate_python;
+mod generate_json_schema;
+mod generator;
+mod cli;
+mod output;
+mod manifest;
+mod watch;
+mod fetch_spec;
+mod spec_cache;
+
+pub use generator::{Generator, GeneratorFactory, GeneratorInput, GeneratorRegistry};
+pub use output::RenderedFile;
+pub use utils::{ErrorProcess, OrderHashMap};
+
+use cli::{Command, get_scaffold_source, normalize_dir, parse_command};
+use fetch_spec::FetchOptions;
+
+pub async fn run(args: Vec<String>, registry: GeneratorRegistry) -> Result<(), ErrorProcess> {
+    let command = parse_command(args);
+
+    match command {
+        Command::Generate { spec, dir_target, check, dry_run, force } => {
+            let (dir_spec, options, only) = spec.into_parts();
+
+            if check {
+                run_gen::run_check(dir_spec, dir_target, options, &registry, only).await?;
+            } else if dry_run {
+                run_gen::run_dry_run(dir_spec, dir_target, options, &registry, only).await?;
+            } else {
+                run_gen::run_gen(dir_spec, dir_target, options, &registry, only, force).await?;
+            }
+        },
+        Command::Check { spec, dir_target } => {
+            let (dir_spec, options, only) = spec.into_parts();
+            run_gen::run_check(dir_spec, dir_target, options, &registry, only).await?;
+        },
+        Command::Validate { spec } => {
+            let (dir_spec, options, only) = spec.into_parts();
+            run_gen::run_validate(dir_spec, options, &registry, only).await?;
+        },
+        Command::Scaffold { dir_spec, base_url, prefix, url, file, merge } => {
+            let source = get_scaffold_source(url, file, merge)?;
+            let dir_spec = normalize_dir(dir_spec);
+            let options = FetchOptions::new(&dir_spec, base_url);
+            scaffold::run_scaffold(dir_spec, options, prefix, source).await?;
+        },
+        Command::Watch { spec, dir_target, force } => {
+            let (dir_spec, options, only) = spec.into_parts();
+            watch::run_watch(dir_spec, dir_target, options, &registry, only, force).await?;
+        },
+        Command::DumpIr { spec } => {
+            let (dir_spec, options, only) = spec.into_parts();
+            run_gen::run_dump_ir(dir_spec, options, &registry, only).await?;
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::output::RenderedFile;
+use crate::utils::ErrorProcess;
+
+pub const MANIFEST_FILE: &str = "openapi.manifest.json";
+
+/*
+    {
+        "prefixes": {
+            "lottery": {
+                "openapi_lottery_getActualLottery.ts": "sha256 of the content"
+            }
+        }
+    }
+*/
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub prefixes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+pub fn content_hash(content: &str) -> String {
+    let hash = Sha256::digest(content.as_bytes());
+    format!("{hash:x}")
+}
+
+#[test]
+fn test_content_hash() {
+    assert_eq!(content_hash(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+}
+
+impl Manifest {
+    pub async fn read(dir_target: &String) -> Result<Manifest, ErrorProcess> {
+        let path = format!("{dir_target}/{MANIFEST_FILE}");
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => {
+                let manifest = serde_json::from_str::<Manifest>(&content).map_err(|err| {
+                    ErrorProcess::message(format!("error decode manifest {path} -> {err}"))
+                })?;
+
+                Ok(manifest)
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(err) => Err(ErrorProcess::message(format!("error read content {path} -> {err}"))),
+        }
+    }
+
+    pub async fn write(&self, dir_target: &String) -> Result<(), ErrorProcess> {
+        let path = format!("{dir_target}/{MANIFEST_FILE}");
+        let content = serde_json::to_string_pretty(self)?;
+        let content = format!("{content}\n");
+
+        if let Ok(old) = tokio::fs::read_to_string(&path).await {
+            if old == content {
+                return Ok(());
+            }
+        }
+
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
+
+    //None -> the prefix has never been generated with the manifest
+    pub fn get_files(&self, prefix: &String) -> Option<&BTreeMap<String, String>> {
+        self.prefixes.get(prefix)
+    }
+
+    pub fn set_files(&mut self, prefix: &str, files: &[RenderedFile]) {
+        let files = files
+            .iter()
+            .map(|file| (file.file_name.clone(), content_hash(&file.content)))
+            .collect();
+
+        self.prefixes.insert(prefix.to_string(), files);
+    }
+}
@@ -2,7 +2,8 @@ use std::{collections::HashMap, fmt::Display};
 use crate::{open_api_type::OpenApiType, utils::{ErrorProcess, OrderHashMap}};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ParamIn {
     Path,
     Body,
@@ -10,16 +11,47 @@ pub enum ParamIn {
     Header,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ParametersType {
     pub where_in: ParamIn,    //'path' | 'body' | 'query' | 'header',
     pub name: String,
     pub api_type: OpenApiType
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyIn {
+    Header,
+    Query,
+    Cookie,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecuritySchemeType {
+    Bearer,
+    Basic,
+    ApiKey {
+        where_in: ApiKeyIn,
+        name: String,                                           //name of the header, query param or cookie
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SecurityType {
+    pub name: String,                                           //key from components.securitySchemes
+    pub scheme: SecuritySchemeType,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct SpecHandlerType {                                    //TODO SpecHandlerType -> OpenApiHandler
     pub parameters: Vec<ParametersType>,
     pub responses: OrderHashMap<u16, OpenApiType>,
+    pub security: Vec<SecurityType>,                            //empty -> public endpoint
+    pub security_optional: bool,                                //true -> the credentials may be omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_error: Option<String>,                         //unsupported security, reported only when the operation is selected
+    pub operation_id: Option<String>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone)]
@@ -37,9 +69,17 @@ pub enum OpenApiMethod {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct OperationRef {
+    pub url: String,
+    pub method: OpenApiMethod,
+}
+
+#[derive(Debug, Clone)]
 pub struct SpecOpenApi {
-    pub paths: HashMap<String, HashMap<OpenApiMethod, SpecHandlerType>>
+    pub paths: HashMap<String, HashMap<OpenApiMethod, SpecHandlerType>>,
+    pub operations: HashMap<String, OperationRef>,              //operationId -> url + method
+    pub ambiguous_operations: HashMap<String, Vec<OperationRef>>, //operationId used by several operations -> all of them, not in operations
 }
 
 
@@ -95,11 +135,31 @@ impl ParamIn {
     }
 }
 
+impl ApiKeyIn {
+    pub fn from_string(name: String) -> Result<ApiKeyIn, ErrorProcess> {
+        let name = name.to_lowercase();
+
+        Ok(match name.as_str() {
+            "header" => ApiKeyIn::Header,
+            "query" => ApiKeyIn::Query,
+            "cookie" => ApiKeyIn::Cookie,
+            _ => {
+                return Err(ErrorProcess::message(format!("unknown ApiKeyIn = {name}")));
+            }
+        })
+    }
+}
+
 impl SpecHandlerType {
     pub fn new() -> SpecHandlerType {
         SpecHandlerType {
             parameters: Vec::new(),
             responses: OrderHashMap::new(),
+            security: Vec::new(),
+            security_optional: false,
+            security_error: None,
+            operation_id: None,
+            tags: Vec::new(),
         }
     }
 
@@ -129,3 +189,19 @@ impl SpecHandlerType {
         Ok(())
     }
 }
+
+impl SpecOpenApi {
+    pub fn get_handler(&self, url: &String, method: &OpenApiMethod) -> Result<&SpecHandlerType, ErrorProcess> {
+        let sub_spec = match self.paths.get(url) {
+            Some(sub_spec) => sub_spec,
+            None => {
+                return Err(ErrorProcess::message(format!("No path in the specification {url}")));
+            }
+        };
+
+        match sub_spec.get(method) {
+            Some(handler) => Ok(handler),
+            None => Err(ErrorProcess::message(format!("No method in the specification {url} {method:?}"))),
+        }
+    }
+}
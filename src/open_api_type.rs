@@ -1,7 +1,9 @@
+use serde::Serialize;
 use crate::utils::OrderHashMap;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum OpenApiType {
     LiteralString {
         value: String,
@@ -50,6 +52,20 @@ impl OpenApiType {
         }
     }
 
+    pub fn is_required(&self) -> bool {
+        match self {
+            Self::LiteralString { required, .. } => *required,
+            Self::String { required } => *required,
+            Self::Number { required } => *required,
+            Self::Boolean { required } => *required,
+            Self::Array { required, .. } => *required,
+            Self::Object { required, .. } => *required,
+            Self::Record { required, .. } => *required,
+            Self::Union { required, .. } => *required,
+            Self::Unknown => true,
+        }
+    }
+
     pub fn object_try_add_literal_field(&mut self, name: impl Into<String>, value: impl Into<String>) {
         let name = name.into();
         let value = value.into();
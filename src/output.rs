@@ -0,0 +1,335 @@
+use std::path::Path;
+use similar::TextDiff;
+
+use crate::manifest::{Manifest, content_hash};
+use crate::utils::{get_file_name, ErrorProcess};
+
+pub struct RenderedFile {
+    pub file_name: String,
+    pub content: String,
+}
+
+/*
+    Used only when the manifest does not know the prefix, see find_untracked.
+*/
+async fn list_files_from_prefix(dir_target: &String, prefix: &String) -> Result<Vec<String>, ErrorProcess> {
+    let start_with = format!("openapi_{prefix}_");
+    let mut read_dir = tokio::fs::read_dir(dir_target).await?;
+
+    let mut out = Vec::new();
+
+    while let Ok(Some(entry)) = read_dir.next_entry().await {
+        let path = entry.path();
+
+        let file_name = get_file_name(&path)?;
+
+        if file_name.starts_with(&start_with) && path.is_file() {
+            out.push(file_name);
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
+pub struct FileChange {
+    pub path: String,
+    pub old: Option<String>,                    //None -> the file does not exist
+    pub new: Option<String>,                    //None -> the file will be deleted
+}
+
+async fn read_current(path: &String) -> Result<Option<String>, ErrorProcess> {
+    match tokio::fs::read(path).await {
+        Ok(content) => Ok(Some(String::from_utf8_lossy(&content).into_owned())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(ErrorProcess::message(format!("error read content {path} -> {err}"))),
+    }
+}
+
+/*
+    Compares the rendered files with dir_target, only the differences are returned.
+    The files recorded in the manifest for this prefix, which are no longer generated, are returned with `new: None`.
+    When the manifest does not know the prefix yet, the leftovers found by find_untracked are deleted as well.
+    prefixes -> every prefix from dir_spec, see find_untracked
+*/
+pub async fn plan_changes(dir_target: &String, prefix: &String, prefixes: &[String], files: &[RenderedFile], manifest: &Manifest) -> Result<Vec<FileChange>, ErrorProcess> {
+    let mut changes = Vec::new();
+
+    for RenderedFile { file_name, content } in files {
+        let path = format!("{dir_target}/{file_name}");
+        let old = read_current(&path).await?;
+
+        if old.as_ref() != Some(content) {
+            changes.push(FileChange { path, old, new: Some(content.clone()) });
+        }
+    }
+
+    if let Some(manifest_files) = manifest.get_files(prefix) {
+        for file_name in manifest_files.keys() {
+            if files.iter().any(|file| file.file_name == *file_name) {
+                continue;
+            }
+
+            let path = format!("{dir_target}/{file_name}");
+
+            if let Some(old) = read_current(&path).await? {
+                changes.push(FileChange { path, old: Some(old), new: None });
+            }
+        }
+    }
+
+    for path in find_untracked(dir_target, prefix, prefixes, files, manifest).await? {
+        if let Some(old) = read_current(&path).await? {
+            changes.push(FileChange { path, old: Some(old), new: None });
+        }
+    }
+
+    Ok(changes)
+}
+
+/*
+    When the manifest does not know the prefix, the openapi_{prefix}_* files which are not generated now
+    are leftovers of a run without the manifest, the same files the cleanup by prefix used to delete.
+    The files of a longer prefix (lottery -> lottery_v2) and the files recorded for another prefix are skipped.
+*/
+async fn find_untracked(dir_target: &String, prefix: &String, prefixes: &[String], files: &[RenderedFile], manifest: &Manifest) -> Result<Vec<String>, ErrorProcess> {
+    if manifest.get_files(prefix).is_some() {
+        return Ok(Vec::new());
+    }
+
+    let start_with = format!("openapi_{prefix}_");
+
+    let longer = prefixes
+        .iter()
+        .chain(manifest.prefixes.keys())
+        .filter(|other| *other != prefix)
+        .map(|other| format!("openapi_{other}_"))
+        .filter(|other_start| other_start.starts_with(&start_with))
+        .collect::<Vec<String>>();
+
+    let mut out = Vec::new();
+
+    for file_name in list_files_from_prefix(dir_target, prefix).await? {
+        let generated = files.iter().any(|file| file.file_name == file_name);
+        let other_prefix = longer.iter().any(|other_start| file_name.starts_with(other_start))
+            || manifest.prefixes.values().any(|other_files| other_files.contains_key(&file_name));
+
+        if !generated && !other_prefix {
+            out.push(format!("{dir_target}/{file_name}"));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+async fn test_dir(name: &str, files: &[(&str, &str)]) -> String {
+    let dir = std::env::temp_dir().join(format!("openapi_gen_{name}_{}", std::process::id()));
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+
+    for (file_name, content) in files {
+        tokio::fs::write(dir.join(file_name), content).await.unwrap();
+    }
+
+    dir.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+fn test_files(files: &[(&str, &str)]) -> Vec<RenderedFile> {
+    files.iter().map(|(file_name, content)| RenderedFile { file_name: file_name.to_string(), content: content.to_string() }).collect()
+}
+
+#[tokio::test]
+async fn test_plan_changes_deletes_files_no_longer_generated() {
+    let dir = test_dir("plan_delete", &[("openapi_lottery_a.ts", "a"), ("openapi_lottery_old.ts", "old")]).await;
+    let prefix = "lottery".to_string();
+
+    let mut manifest = Manifest::default();
+    manifest.set_files(&prefix, &test_files(&[("openapi_lottery_a.ts", "a"), ("openapi_lottery_old.ts", "old")]));
+
+    let changes = plan_changes(&dir, &prefix, &[], &test_files(&[("openapi_lottery_a.ts", "a")]), &manifest).await.unwrap();
+    assert_eq!(changes.iter().map(describe_change).collect::<Vec<String>>(), vec!(format!("extra: {dir}/openapi_lottery_old.ts")));
+    assert!(find_modified(&dir, &prefix, &changes, &manifest).is_empty());
+
+    apply_changes(&changes).await.unwrap();
+    assert!(!Path::new(&format!("{dir}/openapi_lottery_old.ts")).exists());
+    assert!(Path::new(&format!("{dir}/openapi_lottery_a.ts")).exists());
+}
+
+#[tokio::test]
+async fn test_plan_changes_keeps_files_not_in_manifest() {
+    let dir = test_dir("plan_keep", &[("openapi_lottery_a.ts", "a"), ("openapi_lottery_custom.ts", "by hand")]).await;
+    let prefix = "lottery".to_string();
+
+    let mut manifest = Manifest::default();
+    manifest.set_files(&prefix, &test_files(&[("openapi_lottery_a.ts", "a")]));
+
+    let files = test_files(&[("openapi_lottery_a.ts", "a")]);
+    assert!(plan_changes(&dir, &prefix, &[], &files, &manifest).await.unwrap().is_empty());
+    assert!(find_untracked(&dir, &prefix, &["lottery".to_string()], &files, &manifest).await.unwrap().is_empty());
+}
+
+/*
+    Unchanged files are not touched, so their mtime stays the same (no rebuilds in the dev servers).
+*/
+pub async fn apply_changes(changes: &[FileChange]) -> Result<(), ErrorProcess> {
+    for FileChange { path, new, .. } in changes {
+        match new {
+            Some(content) => {
+                //the generator can put the files in subdirectories
+                if let Some(dir) = Path::new(path).parent() {
+                    tokio::fs::create_dir_all(dir).await?;
+                }
+
+                log::info!("generate_js writh to: {path}");
+                tokio::fs::write(path, content).await?;
+            },
+            None => {
+                log::info!("Deleting the old file {path}");
+                tokio::fs::remove_file(path).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/*
+    A file recorded in the manifest whose content no longer matches the hash was modified by hand.
+    When the manifest knows the prefix, an existing file which is not recorded was not generated by us.
+*/
+pub fn find_modified(dir_target: &String, prefix: &String, changes: &[FileChange], manifest: &Manifest) -> Vec<String> {
+    let manifest_files = match manifest.get_files(prefix) {
+        Some(manifest_files) => manifest_files,
+        None => {
+            return Vec::new();
+        }
+    };
+
+    let mut problems = Vec::new();
+
+    for FileChange { path, old, .. } in changes {
+        let old = match old {
+            Some(old) => old,
+            None => continue,
+        };
+
+        let file_name = path.strip_prefix(&format!("{dir_target}/")).unwrap_or(path);
+
+        match manifest_files.get(file_name) {
+            Some(hash) => {
+                if *hash != content_hash(old) {
+                    problems.push(format!("modified by hand: {path}"));
+                }
+            },
+            None => {
+                problems.push(format!("not generated by openapi_gen: {path}"));
+            }
+        }
+    }
+
+    problems
+}
+
+#[tokio::test]
+async fn test_find_modified_refuses_hand_changes() {
+    let dir = test_dir("modified", &[("openapi_lottery_a.ts", "a changed by hand"), ("openapi_lottery_b.ts", "b")]).await;
+    let prefix = "lottery".to_string();
+
+    let mut manifest = Manifest::default();
+    manifest.set_files(&prefix, &test_files(&[("openapi_lottery_a.ts", "a")]));
+
+    let files = test_files(&[("openapi_lottery_a.ts", "a2"), ("openapi_lottery_b.ts", "b2")]);
+    let changes = plan_changes(&dir, &prefix, &[], &files, &manifest).await.unwrap();
+
+    assert_eq!(find_modified(&dir, &prefix, &changes, &manifest), vec!(
+        format!("modified by hand: {dir}/openapi_lottery_a.ts"),
+        format!("not generated by openapi_gen: {dir}/openapi_lottery_b.ts"),
+    ));
+}
+
+#[tokio::test]
+async fn test_find_untracked_longer_prefix() {
+    let dir = test_dir("untracked", &[
+        ("openapi_lottery_a.ts", "a"),
+        ("openapi_lottery_old.ts", "old"),
+        ("openapi_lottery2_a.ts", "a"),
+        ("openapi_lottery_v2_a.ts", "a"),
+        ("openapi_lottery_v3_a.ts", "a"),
+    ]).await;
+    let prefix = "lottery".to_string();
+    let prefixes = vec!("lottery".to_string(), "lottery2".to_string(), "lottery_v2".to_string());
+
+    let mut manifest = Manifest::default();
+    manifest.set_files("lottery_v3", &test_files(&[("openapi_lottery_v3_a.ts", "a")]));
+
+    let files = test_files(&[("openapi_lottery_a.ts", "a")]);
+    let untracked = find_untracked(&dir, &prefix, &prefixes, &files, &manifest).await.unwrap();
+    assert_eq!(untracked, vec!(format!("{dir}/openapi_lottery_old.ts")));
+
+    //the first run with the manifest deletes the leftovers of the cleanup by prefix
+    let changes = plan_changes(&dir, &prefix, &prefixes, &files, &manifest).await.unwrap();
+    assert_eq!(changes.iter().map(describe_change).collect::<Vec<String>>(), vec!(format!("extra: {dir}/openapi_lottery_old.ts")));
+    assert!(find_modified(&dir, &prefix, &changes, &manifest).is_empty());
+
+    //lottery2 with a manifest does not touch the files of lottery
+    manifest.set_files("lottery2", &test_files(&[("openapi_lottery2_a.ts", "a")]));
+    let changes = plan_changes(&dir, &"lottery2".to_string(), &prefixes, &test_files(&[("openapi_lottery2_b.ts", "b")]), &manifest).await.unwrap();
+    assert_eq!(changes.iter().map(describe_change).collect::<Vec<String>>(), vec!(
+        format!("missing: {dir}/openapi_lottery2_b.ts"),
+        format!("extra: {dir}/openapi_lottery2_a.ts"),
+    ));
+}
+
+/*
+    stale: /dir_target/openapi_lottery_getDraw.ts
+    missing: /dir_target/openapi_lottery_getOrders.ts
+    extra: /dir_target/openapi_lottery_old.ts
+*/
+pub fn describe_change(change: &FileChange) -> String {
+    let path = &change.path;
+
+    match (&change.old, &change.new) {
+        (None, _) => format!("missing: {path}"),
+        (Some(_), None) => format!("extra: {path}"),
+        (Some(_), Some(_)) => format!("stale: {path}"),
+    }
+}
+
+pub fn unified_diff(change: &FileChange) -> String {
+    let old = change.old.as_deref().unwrap_or("");
+    let new = change.new.as_deref().unwrap_or("");
+
+    let path = change.path.trim_start_matches('/');
+
+    let old_header = match change.old {
+        Some(_) => format!("a/{path}"),
+        None => "/dev/null".to_string(),
+    };
+
+    let new_header = match change.new {
+        Some(_) => format!("b/{path}"),
+        None => "/dev/null".to_string(),
+    };
+
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string()
+}
+
+#[test]
+fn test_unified_diff() {
+    let change = FileChange {
+        path: "/out/openapi_a_b.ts".into(),
+        old: Some("line1\nline2\n".into()),
+        new: Some("line1\nline3\n".into()),
+    };
+
+    assert_eq!(
+        unified_diff(&change),
+        "--- a/out/openapi_a_b.ts\n+++ b/out/openapi_a_b.ts\n@@ -1,2 +1,2 @@\n line1\n-line2\n+line3\n"
+    );
+}
@@ -1,6 +1,8 @@
 mod parse_spec;
 mod parse_type;
 mod fix_url_param;
+mod parse_security;
+mod rewrite_url;
 
 pub use parse_spec::parse_spec;
-pub use fix_url_param::fix_url_param;
\ No newline at end of file
+pub use rewrite_url::rewrite_url;
\ No newline at end of file
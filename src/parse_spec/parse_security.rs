@@ -0,0 +1,146 @@
+use serde_json::Value;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use crate::utils::ErrorProcess;
+use crate::open_api_spec::{ApiKeyIn, SecuritySchemeType, SecurityType};
+
+//security: [{ "bearerAuth": [] }, { "apiKey": [] }] -> list of alternatives
+pub type SecurityRequirementSpec = Vec<HashMap<String, Vec<String>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SecuritySchemeSpec {
+    r#type: String,
+    scheme: Option<String>,
+    r#in: Option<String>,
+    name: Option<String>,
+}
+
+/*
+    (the authentication parameters, true -> the credentials may be omitted)
+    An empty list of requirements means a public endpoint, an empty alternative means an optional authentication:
+    [{ "bearerAuth": [] }, {}] -> ([bearerAuth], true)
+    Only one non-empty alternative is supported, parse_handler keeps the error in security_error so that
+    it is reported only when the operation is selected.
+*/
+pub fn parse_security(requirements: &SecurityRequirementSpec, spec: &Value) -> Result<(Vec<SecurityType>, bool), ErrorProcess> {
+    let optional = requirements.iter().any(|requirement| requirement.is_empty());
+    let alternatives = requirements.iter().filter(|requirement| !requirement.is_empty()).collect::<Vec<_>>();
+
+    let first = match alternatives.as_slice() {
+        [] => {
+            return Ok((Vec::new(), false));
+        },
+        [first] => first,
+        _ => {
+            let list = alternatives
+                .iter()
+                .map(|requirement| {
+                    let mut names = requirement.keys().cloned().collect::<Vec<String>>();
+                    names.sort();
+                    names.join(" + ")
+                })
+                .collect::<Vec<String>>()
+                .join(" | ");
+
+            return Err(ErrorProcess::message(format!("Alternative security requirements are not supported: {list}")));
+        }
+    };
+
+    let mut names = first.keys().collect::<Vec<&String>>();
+    names.sort();
+
+    let mut out = Vec::new();
+
+    for name in names {
+        let scheme_raw = spec
+            .get("components")
+            .and_then(|components| components.get("securitySchemes"))
+            .and_then(|schemes| schemes.get(name));
+
+        let scheme_raw = match scheme_raw {
+            Some(scheme_raw) => scheme_raw,
+            None => {
+                return Err(ErrorProcess::message(format!("Missing security scheme {name} in components.securitySchemes")));
+            }
+        };
+
+        let scheme = parse_security_scheme(name, scheme_raw)?;
+
+        out.push(SecurityType {
+            name: name.clone(),
+            scheme,
+        });
+    }
+
+    Ok((out, optional))
+}
+
+fn parse_security_scheme(name: &str, scheme_raw: &Value) -> Result<SecuritySchemeType, ErrorProcess> {
+    let scheme_spec = serde_json::from_value::<SecuritySchemeSpec>(scheme_raw.clone())?;
+
+    match scheme_spec.r#type.as_str() {
+        "http" => {
+            let scheme = scheme_spec.scheme.unwrap_or_default().to_lowercase();
+
+            match scheme.as_str() {
+                "bearer" => Ok(SecuritySchemeType::Bearer),
+                "basic" => Ok(SecuritySchemeType::Basic),
+                _ => Err(ErrorProcess::message(format!("Security scheme {name}: unsupported http scheme = {scheme}"))),
+            }
+        },
+        "apiKey" => {
+            let (where_in, key_name) = match (scheme_spec.r#in, scheme_spec.name) {
+                (Some(where_in), Some(key_name)) => (where_in, key_name),
+                _ => {
+                    return Err(ErrorProcess::message(format!("Security scheme {name}: apiKey requires 'in' and 'name'")));
+                }
+            };
+
+            Ok(SecuritySchemeType::ApiKey {
+                where_in: ApiKeyIn::from_string(where_in)?,
+                name: key_name,
+            })
+        },
+        //the access token obtained by these flows is sent as a bearer token
+        "oauth2" | "openIdConnect" => Ok(SecuritySchemeType::Bearer),
+        other => Err(ErrorProcess::message(format!("Security scheme {name}: unsupported type = {other}"))),
+    }
+}
+
+#[test]
+fn test_parse_security() {
+    let spec = serde_json::json!({
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+                "partnerKey": { "type": "apiKey", "in": "header", "name": "X-Partner-Key" }
+            }
+        }
+    });
+
+    let requirements: SecurityRequirementSpec = serde_json::from_value(serde_json::json!([
+        { "partnerKey": [], "bearerAuth": [] },
+        {}
+    ])).unwrap();
+
+    assert_eq!(parse_security(&requirements, &spec).unwrap(), (vec!(
+        SecurityType {
+            name: "bearerAuth".into(),
+            scheme: SecuritySchemeType::Bearer,
+        },
+        SecurityType {
+            name: "partnerKey".into(),
+            scheme: SecuritySchemeType::ApiKey { where_in: ApiKeyIn::Header, name: "X-Partner-Key".into() },
+        },
+    ), true));
+
+    assert_eq!(parse_security(&Vec::new(), &spec).unwrap(), (Vec::new(), false));
+
+    let alternatives: SecurityRequirementSpec = serde_json::from_value(serde_json::json!([
+        { "bearerAuth": [] },
+        { "partnerKey": [] }
+    ])).unwrap();
+
+    assert!(parse_security(&alternatives, &spec).is_err());
+}
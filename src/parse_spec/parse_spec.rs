@@ -1,16 +1,18 @@
 use super::parse_type::parse_type;
+use super::parse_security::{parse_security, SecurityRequirementSpec};
 use serde_json::Value;
 use serde::{Serialize, Deserialize};
 // use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::utils::ErrorProcess;
-use crate::open_api_spec::{SpecHandlerType, OpenApiMethod, SpecOpenApi};
+use crate::open_api_spec::{OperationRef, SpecHandlerType, OpenApiMethod, SpecOpenApi};
 
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Spec {
     paths: HashMap<String, HashMap<String, Option<Value>>>,
+    security: Option<SecurityRequirementSpec>,
 }
 
 
@@ -30,6 +32,9 @@ pub fn parse_spec(data: String) -> Result<SpecOpenApi, ErrorProcess> {
     let spec = serde_json::from_value::<Spec>(spec_raw.clone())?;
 
     let mut paths: HashMap<String, HashMap<OpenApiMethod, SpecHandlerType>> = HashMap::new();
+    let mut operations: HashMap<String, OperationRef> = HashMap::new();
+    let mut ambiguous_operations: HashMap<String, Vec<OperationRef>> = HashMap::new();
+    let global_security = spec.security.unwrap_or_default();
 
     for (path, path_body) in spec.paths {
         let mut path_methods: HashMap<OpenApiMethod, SpecHandlerType> = HashMap::new();
@@ -38,7 +43,23 @@ pub fn parse_spec(data: String) -> Result<SpecOpenApi, ErrorProcess> {
         for (method_name, method_body) in path_body {
             if let Some(method_body) = method_body {
                 let method_name = OpenApiMethod::from_string(method_name)?;
-                let method_body = parse_handler(method_body, &spec_raw)?;
+                let method_body = parse_handler(method_body, &spec_raw, &global_security)?;
+
+                if let Some(operation_id) = &method_body.operation_id {
+                    let operation = OperationRef {
+                        url: path.clone(),
+                        method: method_name.clone(),
+                    };
+
+                    //a duplicate operationId is an error only when it is referenced by the wanted spec
+                    if let Some(list) = ambiguous_operations.get_mut(operation_id) {
+                        list.push(operation);
+                    } else if let Some(first) = operations.remove(operation_id) {
+                        ambiguous_operations.insert(operation_id.clone(), vec!(first, operation));
+                    } else {
+                        operations.insert(operation_id.clone(), operation);
+                    }
+                }
 
                 path_methods.insert(method_name, method_body);
             }
@@ -48,7 +69,9 @@ pub fn parse_spec(data: String) -> Result<SpecOpenApi, ErrorProcess> {
     }
 
     Ok(SpecOpenApi {
-        paths
+        paths,
+        operations,
+        ambiguous_operations,
     })
 }
 
@@ -58,6 +81,10 @@ struct HandlerSpec {
     #[serde(rename = "requestBody")]
     request_body: Option<Value>,
     responses: Option<HashMap<String, Value>>,      //200 -> typ, 300 -> typ
+    security: Option<SecurityRequirementSpec>,      //overrides the global security
+    #[serde(rename = "operationId")]
+    operation_id: Option<String>,
+    tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,7 +99,7 @@ struct RequestBody {
     required: Option<bool>,
 }
 
-pub fn parse_handler(body_raw: Value, spec: &Value) -> Result<SpecHandlerType, ErrorProcess> {
+pub fn parse_handler(body_raw: Value, spec: &Value, global_security: &SecurityRequirementSpec) -> Result<SpecHandlerType, ErrorProcess> {
 
     let mut result = SpecHandlerType::new();
 
@@ -103,5 +130,19 @@ pub fn parse_handler(body_raw: Value, spec: &Value) -> Result<SpecHandlerType, E
         }
     }
 
+    let security = body.security.as_ref().unwrap_or(global_security);
+    match parse_security(security, spec) {
+        Ok((security, optional)) => {
+            result.security = security;
+            result.security_optional = optional;
+        },
+        Err(err) => {
+            result.security_error = Some(err.to_string());
+        }
+    }
+
+    result.operation_id = body.operation_id;
+    result.tags = body.tags.unwrap_or_default();
+
     Ok(result)
 }
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use regex::Regex;
+
+use crate::open_api_spec::{OpenApiMethod, OperationRef, ParamIn, ParametersType, SpecHandlerType, SpecOpenApi};
+use crate::open_api_type::OpenApiType;
+use crate::read_wanted_spec::{InjectParamType, RewriteRule};
+use crate::select_methods::glob_match;
+use crate::utils::ErrorProcess;
+use super::fix_url_param::fix_url;
+
+fn get_api_type(param_type: &InjectParamType, required: bool) -> Result<OpenApiType, ErrorProcess> {
+    let api_type = match param_type {
+        InjectParamType::String => OpenApiType::String { required: true },
+        InjectParamType::Number => OpenApiType::Number { required: true },
+        InjectParamType::Enum(values) => {
+            let mut list = values
+                .iter()
+                .map(|value| OpenApiType::LiteralString { value: value.clone(), required: true })
+                .collect::<Vec<OpenApiType>>();
+
+            match list.len() {
+                0 => {
+                    return Err(ErrorProcess::message("inject: the enum type requires at least one value"));
+                },
+                1 => list.pop().unwrap(),
+                _ => OpenApiType::Union { required: true, list },
+            }
+        }
+    };
+
+    Ok(api_type.set_required(required))
+}
+
+/*
+    /lottery/{universe}/draws/{id}
+    ["universe", "id"]
+*/
+fn get_path_params(url: &str) -> Vec<String> {
+    url.split('/')
+        .filter(|chunk| chunk.starts_with('{') && chunk.ends_with('}'))
+        .map(|chunk| chunk[1..chunk.len() - 1].to_string())
+        .collect()
+}
+
+#[test]
+fn test_get_path_params() {
+    assert_eq!(get_path_params("/lottery/{universe}/draws/{id}"), vec!("universe".to_string(), "id".to_string()));
+    assert_eq!(get_path_params("/lottery"), Vec::<String>::new());
+}
+
+/*
+    Not strict: an existing parameter with that name is kept as it is
+*/
+fn add_param(handler: &mut SpecHandlerType, where_in: ParamIn, name: &String, api_type: &OpenApiType, strict: bool) -> Result<(), ErrorProcess> {
+    for param in handler.parameters.iter_mut() {
+        if param.name == *name {
+            if !strict {
+                return Ok(());
+            }
+
+            if param.where_in != where_in {
+                let param_in = &param.where_in;
+                return Err(ErrorProcess::message(format!("inject: the parameter {name} already exists in {param_in:?}")));
+            }
+
+            param.api_type = api_type.clone();
+            return Ok(());
+        }
+    }
+
+    handler.parameters.push(ParametersType {
+        where_in,
+        name: name.clone(),
+        api_type: api_type.clone(),
+    });
+
+    Ok(())
+}
+
+/*
+    The rewritten paths must not collide with each other, the path parameters that appear after rewriting
+    are added to the handlers with the type `param_type`.
+    Not strict (the legacy fix_url_param): a rule which matches no path does nothing.
+*/
+fn rewrite_paths(
+    spec: &mut SpecOpenApi,
+    methods: &mut HashMap<String, OperationRef>,
+    rule_name: &str,
+    param_type: &OpenApiType,
+    strict: bool,
+    rewrite: impl Fn(&String) -> Option<String>,
+) -> Result<(), ErrorProcess> {
+    let mut paths: HashMap<String, HashMap<OpenApiMethod, SpecHandlerType>> = HashMap::new();
+    let mut matched = 0;
+
+    for (path, handlers) in spec.paths.iter() {
+        let mut handlers = handlers.clone();
+
+        let new_path = match rewrite(path) {
+            Some(new_path) => new_path,
+            None => path.clone(),
+        };
+
+        if new_path != *path {
+            matched += 1;
+
+            let old_params = get_path_params(path);
+
+            for param_name in get_path_params(&new_path) {
+                if old_params.contains(&param_name) {
+                    continue;
+                }
+
+                for (_, handler) in handlers.iter_mut() {
+                    add_param(handler, ParamIn::Path, &param_name, param_type, strict)?;
+                }
+            }
+        }
+
+        if paths.contains_key(&new_path) {
+            return Err(ErrorProcess::message(format!("{rule_name}: the path {path} collides with an existing path {new_path}")));
+        }
+
+        paths.insert(new_path, handlers);
+    }
+
+    if matched == 0 {
+        if !strict {
+            return Ok(());
+        }
+
+        return Err(ErrorProcess::message(format!("{rule_name}: the rule does not match any path")));
+    }
+
+    spec.paths = paths;
+
+    for (_, operation) in spec.operations.iter_mut().chain(methods.iter_mut()) {
+        if let Some(new_url) = rewrite(&operation.url) {
+            operation.url = new_url;
+        }
+    }
+
+    Ok(())
+}
+
+fn inject_query(spec: &mut SpecOpenApi, name: &String, api_type: &OpenApiType, path_glob: &Option<String>) -> Result<(), ErrorProcess> {
+    let mut matched = 0;
+
+    for (path, handlers) in spec.paths.iter_mut() {
+        if let Some(path_glob) = path_glob {
+            if !glob_match(path_glob, path) {
+                continue;
+            }
+        }
+
+        for (_, handler) in handlers.iter_mut() {
+            add_param(handler, ParamIn::Query, name, api_type, true)?;
+            matched += 1;
+        }
+    }
+
+    if matched == 0 {
+        let path_glob = path_glob.as_deref().unwrap_or("*");
+        return Err(ErrorProcess::message(format!("inject_query {name}: the rule does not match any path ({path_glob})")));
+    }
+
+    Ok(())
+}
+
+/*
+    The rule is applied to the specification and to the urls of the wanted methods,
+    so in the wanted spec the paths are given in the form from the original specification.
+*/
+pub fn rewrite_url(spec: &mut SpecOpenApi, methods: &mut HashMap<String, OperationRef>, rule: &RewriteRule) -> Result<(), ErrorProcess> {
+    let string_type = OpenApiType::String { required: true };
+
+    match rule {
+        RewriteRule::Regex { pattern, replace } => {
+            let regex = Regex::new(pattern).map_err(|err| {
+                ErrorProcess::message(format!("regex {pattern}: {err}"))
+            })?;
+
+            rewrite_paths(spec, methods, &format!("regex {pattern}"), &string_type, true, |url| {
+                if regex.is_match(url) {
+                    Some(regex.replace(url, replace.as_str()).into_owned())
+                } else {
+                    None
+                }
+            })
+        },
+        RewriteRule::StripPrefix { prefix } => {
+            let prefix = prefix.trim_end_matches('/');
+
+            //whole segments only: /internal does not match /internal-api/info
+            rewrite_paths(spec, methods, &format!("strip_prefix {prefix}"), &string_type, true, |url| {
+                match url.strip_prefix(prefix) {
+                    Some("") => Some("/".to_string()),
+                    Some(rest) if rest.starts_with('/') => Some(rest.to_string()),
+                    _ => None,
+                }
+            })
+        },
+        RewriteRule::AddPrefix { prefix } => {
+            let prefix = prefix.trim_end_matches('/');
+
+            rewrite_paths(spec, methods, &format!("add_prefix {prefix}"), &string_type, true, |url| {
+                Some(format!("{prefix}{url}"))
+            })
+        },
+        RewriteRule::InjectParam { from, to, param_type } => {
+            let api_type = get_api_type(param_type, true)?;
+
+            rewrite_paths(spec, methods, &format!("inject_param {from} -> {to}"), &api_type, true, |url| {
+                Some(fix_url(url, from, to))
+            })
+        },
+        RewriteRule::FixUrlParam { from, to } => {
+            rewrite_paths(spec, methods, &format!("fix_url_param {from} -> {to}"), &string_type, false, |url| {
+                Some(fix_url(url, from, to))
+            })
+        },
+        RewriteRule::InjectQuery { name, param_type, required, path } => {
+            let api_type = get_api_type(param_type, *required)?;
+            inject_query(spec, name, &api_type, path)
+        },
+    }
+}
+
+#[test]
+fn test_rewrite_url_fix_url_param_lenient() {
+    let mut handler = SpecHandlerType::new();
+    handler.add_param("universe", "query", OpenApiType::Number { required: true }, true).unwrap();
+
+    let mut spec = SpecOpenApi {
+        paths: HashMap::from([("/fixed/draws".to_string(), HashMap::from([(OpenApiMethod::Get, handler)]))]),
+        operations: HashMap::new(),
+        ambiguous_operations: HashMap::new(),
+    };
+    let mut methods = HashMap::new();
+
+    let fix = |from: &str| RewriteRule::FixUrlParam { from: from.into(), to: "universe".into() };
+    let inject = RewriteRule::InjectParam { from: "missing".into(), to: "universe".into(), param_type: InjectParamType::String };
+
+    rewrite_url(&mut spec, &mut methods, &fix("missing")).unwrap();
+    assert!(rewrite_url(&mut spec, &mut methods, &inject).is_err());
+
+    rewrite_url(&mut spec, &mut methods, &fix("fixed")).unwrap();
+    let handler = &spec.paths["/{universe}/draws"][&OpenApiMethod::Get];
+    assert_eq!(handler.parameters.len(), 1);
+    assert_eq!(handler.parameters[0].where_in, ParamIn::Query);
+}
+
+#[test]
+fn test_rewrite_url_strip_prefix_segments() {
+    let handler = || HashMap::from([(OpenApiMethod::Get, SpecHandlerType::new())]);
+
+    let mut spec = SpecOpenApi {
+        paths: HashMap::from([
+            ("/internal/info".to_string(), handler()),
+            ("/internal-api/info".to_string(), handler()),
+        ]),
+        operations: HashMap::new(),
+        ambiguous_operations: HashMap::new(),
+    };
+    let mut methods = HashMap::new();
+
+    rewrite_url(&mut spec, &mut methods, &RewriteRule::StripPrefix { prefix: "/internal/".into() }).unwrap();
+
+    let mut paths = spec.paths.keys().cloned().collect::<Vec<String>>();
+    paths.sort();
+    assert_eq!(paths, vec!("/info".to_string(), "/internal-api/info".to_string()));
+}
@@ -1,5 +1,6 @@
-use std::{collections::{HashMap}, fs, path::PathBuf};
+use std::{collections::{BTreeMap, HashMap}, fs, path::PathBuf};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{open_api_spec::OpenApiMethod, utils::{get_file_name, ErrorProcess}};
 
@@ -25,14 +26,14 @@ pub fn read_wanted_spec(dir_spec: &String) -> Result<HashMap<String, WantedSpec>
                 continue;
             }
         } else {
-            log::error!("I ignore reading is not a file: {path:?}");
+            log::debug!("I ignore reading is not a file: {path:?}");
         }
     }
 
     Ok(out)
 }
 
-fn parse_spec_name(path: &PathBuf) -> Result<Option<String>, ErrorProcess> {
+pub fn parse_spec_name(path: &PathBuf) -> Result<Option<String>, ErrorProcess> {
     let file_name = get_file_name(path)?;
 
     let mut file_name_chunks: Vec<&str> = file_name.split('.').collect();
@@ -59,40 +60,300 @@ fn test_parse_spec_name() {
     assert_eq!(name, "ttrree");
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum WantedSource {
     #[serde(rename = "url")]
     Url {
         url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         fix_url_param: Option<Vec<FixUrlParamItem>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rewrite: Option<Vec<RewriteRule>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        http: Option<HttpConfig>,
     },
     #[serde(rename = "file")]
     File {
         file: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         fix_url_param: Option<Vec<FixUrlParamItem>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rewrite: Option<Vec<RewriteRule>>,
     }
 }
 
+impl WantedSource {
+    //fix_url_param is applied before the rewrite rules, as the lenient form of the inject_param rule
+    pub fn get_rewrite_rules(&self) -> Vec<RewriteRule> {
+        let (fix_url_param, rewrite) = match self {
+            Self::Url { fix_url_param, rewrite, .. } => (fix_url_param, rewrite),
+            Self::File { fix_url_param, rewrite, .. } => (fix_url_param, rewrite),
+        };
+
+        let mut out = Vec::new();
+
+        for FixUrlParamItem { from, to } in fix_url_param.iter().flatten() {
+            out.push(RewriteRule::FixUrlParam {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+
+        out.extend(rewrite.iter().flatten().cloned());
+        out
+    }
+}
+
+// {
+//     "headers": { "Authorization": "Bearer ${SPEC_TOKEN}" },
+//     "timeout": 30,
+//     "retry": { "attempts": 3, "backoff_ms": 500 },
+//     "ca_cert": "certs/internal-ca.pem",
+//     "proxy": "http://proxy.local:3128"
+// }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,          //${NAME} is replaced with the environment variable NAME
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,                       //seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,                    //pem file, relative to dir_spec
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    pub attempts: u32,                              //retries after the first failed request
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,                            //doubled after every retry
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-pub struct WantedMethod {
-    pub method: OpenApiMethod,
-    pub url: String,
+#[serde(untagged)]
+pub enum WantedMethod {
+    Path {
+        method: OpenApiMethod,
+        url: String,
+    },
+    OperationId {
+        #[serde(rename = "operationId")]
+        operation_id: String,
+    },
 }
 
+// All given conditions have to match:
+// {"tags": ["lottery"], "path": "/lottery/**", "methods": ["get"]}
 #[derive(Debug, Serialize, Deserialize)]
+pub struct WantedSelector {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,                          //at least one tag has to match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,                       //glob, "*" - one segment, "**" - any number of segments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<OpenApiMethod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FixUrlParamItem {
     pub from: String,
     pub to: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum InjectParamType {
+    #[default]
+    #[serde(rename = "string")]
+    String,
+    #[serde(rename = "number")]
+    Number,
+    #[serde(rename = "enum")]
+    Enum(Vec<String>),                              //{"enum": ["pl", "en"]}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RewriteRule {
+    #[serde(rename = "regex")]
+    Regex {
+        pattern: String,
+        replace: String,                            //"$1", "${name}" refer to the groups from the pattern
+    },
+    #[serde(rename = "strip_prefix")]
+    StripPrefix {
+        prefix: String,                             //whole path segments, "/internal" leaves "/internal-api/info" as it is
+    },
+    #[serde(rename = "add_prefix")]
+    AddPrefix {
+        prefix: String,
+    },
+    #[serde(rename = "inject_param")]
+    InjectParam {
+        from: String,                               //path segment replaced with {to}
+        to: String,
+        #[serde(default)]
+        param_type: InjectParamType,
+    },
+    #[serde(rename = "inject_query")]
+    InjectQuery {
+        name: String,
+        #[serde(default)]
+        param_type: InjectParamType,
+        #[serde(default)]
+        required: bool,
+        path: Option<String>,                       //glob, by default the parameter is added to every path
+    },
+    #[serde(skip)]
+    FixUrlParam {                                   //an item of fix_url_param: no matching path is not an error, an existing parameter is kept
+        from: String,
+        to: String,
+    },
+}
+
+// "generator": { "type": "io_ts" }
+// "generator": { "type": "zod", "runtime": { "type": "native_fetch" } }
+// "generator": { "type": "custom", "name": "kotlin", "options": { "package": "com.example.api" } }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeneratorConfig {
+    IoTs(TsOptions),                                //io-ts decoders
+    Zod(TsOptions),                                 //zod decoders
+    TypesOnly,                                      //only the TS type declarations (.d.ts), no runtime code
+    Template(TemplateOptions),                      //the file layout comes from a user template
+    RustClient,                                     //serde types and async reqwest functions
+    RustServer,                                     //serde types, axum handlers and a trait with a method per operation
+    PythonClient,                                   //pydantic v2 models and httpx functions
+    JsonSchema(JsonSchemaOptions),                  //JSON Schema 2020-12 of the params and of every response
+    Custom {                                        //a backend registered with GeneratorRegistry::register
+        name: String,
+        #[serde(default)]
+        options: Value,
+    },
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> GeneratorConfig {
+        GeneratorConfig::IoTs(TsOptions::default())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TsOptions {
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    #[serde(default)]
+    pub index: bool,                                //openapi_{prefix}_index.ts re-exporting every operation, with a registry of the operations
+}
+
+// "generator": { "type": "template", "template": "templates/request.ts.j2", "codec": "zod" }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateOptions {
+    pub template: String,                           //minijinja template, relative to dir_spec
+    #[serde(default = "default_template_file_name")]
+    pub file_name: String,                          //also a template, with the same context
+    #[serde(default)]
+    pub codec: CodecType,                           //the library of the rendered decoders
+}
+
+// "generator": { "type": "json_schema", "refs": "defs" }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonSchemaOptions {
+    #[serde(default)]
+    pub refs: SchemaRefs,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaRefs {
+    #[default]
+    Inline,                                         //the nested objects are written in place
+    Defs,                                           //the nested objects go to "$defs" and are referenced with "$ref"
+}
+
+fn default_template_file_name() -> String {
+    "openapi_{{ prefix }}_{{ method_name }}.ts".into()
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodecType {
+    #[default]
+    IoTs,
+    Zod,
+}
+
+// "runtime": { "type": "imports", "fetch_path": "@org/http/fetch", "time_log_path": null }
+// "runtime": { "type": "native_fetch" }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuntimeConfig {
+    Imports(RuntimeImports),                        //the helpers are imported from the project
+    NativeFetch,                                    //the helpers are generated to openapi_{prefix}_runtime.ts, based on the native fetch
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> RuntimeConfig {
+        RuntimeConfig::Imports(RuntimeImports::default())
+    }
+}
+
+//The omitted fields keep the default values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeImports {
+    pub fetch_path: String,
+    pub fetch_name: String,                         //(method, { url, body, backendToken?, headers?, timeout }) => Promise<response>
+    pub fetch_response_type: String,                //{ status: number, body: string }
+    pub time_log_path: Option<String>,              //null -> the requests are not timed
+    pub time_log_name: String,
+    pub json_parse_path: String,
+    pub json_parse_name: String,                    //(text) => { type: 'json', json } | { type: 'text', text }
+}
+
+impl Default for RuntimeImports {
+    fn default() -> RuntimeImports {
+        RuntimeImports {
+            fetch_path: "src_common/common/fetch".into(),
+            fetch_name: "fetchGeneralRaw".into(),
+            fetch_response_type: "FetchGeneralRawResponseType".into(),
+            time_log_path: Some("src_common/server/webDriver/logFormat".into()),
+            time_log_name: "ApiTimeLog".into(),
+            json_parse_path: "src_common/common/jsonParse".into(),
+            json_parse_name: "jsonParse".into(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WantedSpec {
     pub source: WantedSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<GeneratorConfig>,         //io_ts by default
+    #[serde(flatten)]
+    pub selection: WantedSelection,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WantedSelection {
+    #[serde(default)]
     pub methods: HashMap<String, WantedMethod>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operations: Vec<String>,                    //operationId list, the name is derived from the operationId
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub select: Vec<WantedSelector>,                //bulk selection, the name is derived from the operationId or the path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<WantedSelector>,               //removes operations found by "select"
 }
 
-fn parse_wanted_spec(content: String) -> Result<WantedSpec, ErrorProcess> {
+pub fn parse_wanted_spec(content: String) -> Result<WantedSpec, ErrorProcess> {
     let spec = serde_json::from_str::<WantedSpec>(&content)?;
     Ok(spec)
 }
@@ -101,7 +362,22 @@ fn parse_wanted_spec(content: String) -> Result<WantedSpec, ErrorProcess> {
 {
     "source": {
         "type": "url",
-        "url": "/lottery-integration/meta/open-api"
+        "url": "/lottery-integration/meta/open-api",
+        "rewrite": [
+            { "type": "strip_prefix", "prefix": "/internal" },
+            { "type": "inject_param", "from": "fixed", "to": "universe", "param_type": { "enum": ["pl", "com"] } },
+            { "type": "inject_query", "name": "lang", "param_type": "string", "required": true }
+        ],
+        "http": {
+            "headers": { "Authorization": "Bearer ${SPEC_TOKEN}" },
+            "timeout": 30,
+            "retry": { "attempts": 3, "backoff_ms": 500 }
+        }
+    },
+    "generator": {
+        "type": "zod",
+        "runtime": { "type": "native_fetch" },
+        "index": true
     },
     "methods": {
         "getActualLottery": {
@@ -131,7 +407,19 @@ fn parse_wanted_spec(content: String) -> Result<WantedSpec, ErrorProcess> {
         "getSettledLottery": {
             "url": "/lottery/tickets/settled/{universe}/{accountId}",
             "method": "get"
+        },
+        "getLotteryResults": {
+            "operationId": "getLotteryResults"
         }
-    }
+    },
+    "operations": [
+        "getLotteryStats"
+    ],
+    "select": [
+        { "tags": ["lottery-admin"], "methods": ["get"] }
+    ],
+    "exclude": [
+        { "path": "/lottery/admin/reset" }
+    ]
 }
 */
\ No newline at end of file
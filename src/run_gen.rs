@@ -1,126 +1,190 @@
 
 use std::collections::HashMap;
 
-use crate::generate_js::generate_js;
-use crate::utils::{get_file_name, ErrorProcess};
-use crate::{open_api_spec::SpecOpenApi};
-use crate::read_wanted_spec::{FixUrlParamItem, WantedMethod, WantedSource, WantedSpec, read_wanted_spec};
-use crate::parse_spec::{fix_url_param, parse_spec};
-
-pub async fn run_gen(dir_spec: String, dir_target: String, base_url: String, target_spec: String) -> Result<(), ErrorProcess> {
-    let mut wanted_spec = read_wanted_spec(&dir_spec)?;
+use crate::fetch_spec::FetchOptions;
+use crate::generator::{Generator, GeneratorInput, GeneratorRegistry};
+use crate::manifest::Manifest;
+use crate::output::{RenderedFile, apply_changes, describe_change, find_modified, plan_changes, unified_diff};
+use crate::utils::ErrorProcess;
+use crate::open_api_spec::{OperationRef, SpecOpenApi};
+use crate::read_wanted_spec::{WantedSpec, read_wanted_spec};
+use crate::validate::{PreparedSpec, prepare_specs};
+
+/*
+    An empty list of prefixes means all prefixes.
+*/
+pub fn read_wanted_spec_for(dir_spec: &String, only: &[String]) -> Result<HashMap<String, WantedSpec>, ErrorProcess> {
+    let mut wanted_spec = read_wanted_spec(dir_spec)?;
+
+    for prefix in only {
+        if !wanted_spec.contains_key(prefix) {
+            return Err(ErrorProcess::message(format!("Unknown prefix {prefix}, missing file {dir_spec}{prefix}.spec.json")));
+        }
+    }
 
-    if target_spec != "all" {
+    if !only.is_empty() {
         wanted_spec.retain(|key, _| {
-            *key == target_spec
+            only.contains(key)
         });
     }
 
-    for (prefix, WantedSpec { source, mut methods }) in wanted_spec {
-        let (mut spec, fix_url_param_opt) = get_spec(&dir_spec, &base_url, source).await?;
+    Ok(wanted_spec)
+}
+
+/*
+    Every prefix from dir_spec, also the ones not selected with --only
+*/
+fn all_prefixes(dir_spec: &String) -> Result<Vec<String>, ErrorProcess> {
+    Ok(read_wanted_spec(dir_spec)?.into_keys().collect())
+}
+
+pub async fn run_gen(dir_spec: String, dir_target: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>, force: bool) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let prefixes = all_prefixes(&dir_spec)?;
+    let prepared = prepare_specs(&dir_spec, &options, registry, wanted_spec).await?;
+
+    let mut manifest = Manifest::read(&dir_target).await?;
+    let mut rendered = Vec::new();
+    let mut problems = Vec::new();
+
+    for PreparedSpec { prefix, spec, methods, generator } in prepared {
+        let files = render_prefix(&dir_spec, &prefix, &spec, &methods, generator.as_ref())?;
+        let changes = plan_changes(&dir_target, &prefix, &prefixes, &files, &manifest).await?;
+
+        problems.extend(find_modified(&dir_target, &prefix, &changes, &manifest));
+        rendered.push((prefix, files, changes));
+    }
+
+    if !problems.is_empty() {
+        for problem in problems.iter() {
+            eprintln!("{problem}");
+        }
 
-        if let Some(fix_url_param_list) = fix_url_param_opt {
-            for FixUrlParamItem { from, to } in fix_url_param_list.into_iter() {
-                fix_url_param(&mut spec, &mut methods, from, to)
-            }
+        if !force {
+            let count = problems.len();
+            return Err(ErrorProcess::message(format!("Refusing to overwrite files changed by hand ({count}), use --force")));
         }
+    }
+
+    for (prefix, files, changes) in rendered {
+        apply_changes(&changes).await?;
 
-        remove_files_from_prefix(&dir_target, &prefix).await?;
-        run_gen_for_prefix(&dir_target, prefix, spec, methods).await?;
+        manifest.set_files(&prefix, &files);
+        manifest.write(&dir_target).await?;
     }
 
     Ok(())
 }
 
-async fn get_spec(dir_spec: &String, base_url: &String, source: WantedSource) -> Result<(SpecOpenApi, Option<Vec<FixUrlParamItem>>), ErrorProcess> {
-    let (spec_source, fix_url_param) = match source {
-        WantedSource::File { file, fix_url_param } => {
-            let file_full = format!("{dir_spec}{file}");
+/*
+    Renders everything in memory and compares it with dir_target, nothing is written or deleted.
+*/
+pub async fn run_check(dir_spec: String, dir_target: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let prefixes = all_prefixes(&dir_spec)?;
+    let prepared = prepare_specs(&dir_spec, &options, registry, wanted_spec).await?;
 
-            log::info!("read file with spec: {file_full}");
+    let manifest = Manifest::read(&dir_target).await?;
+    let mut problems = Vec::new();
 
-            let content = tokio::fs::read_to_string(&file_full).await.map_err({
-                let file_full = file_full.clone();
-                move |err| {
-                    ErrorProcess::message(format!("error read content {file_full} -> {err}"))
-                }
-            })?;
+    for PreparedSpec { prefix, spec, methods, generator } in prepared {
+        let files = render_prefix(&dir_spec, &prefix, &spec, &methods, generator.as_ref())?;
 
-            (content, fix_url_param)
-        },
-        WantedSource::Url { url, fix_url_param } => {
-            let url_full = format!("{base_url}{url}");
+        for change in plan_changes(&dir_target, &prefix, &prefixes, &files, &manifest).await? {
+            problems.push(describe_change(&change));
+        }
+    }
 
-            log::info!("read url with spec: {url_full}");
+    if !problems.is_empty() {
+        for problem in problems.iter() {
+            println!("{problem}");
+        }
 
-            let response = reqwest::get(&url_full).await.map_err({
-                let url_full = url_full.clone();
-                move |err| {
-                    ErrorProcess::message(format!("error fetch {url_full} -> {err}"))
-                }
-            })?;
+        let count = problems.len();
+        return Err(ErrorProcess::message(format!("The generated files are out of date, problems found: {count}")));
+    }
 
-            let text = response.text().await.map_err(move |err| {
-                ErrorProcess::message(format!("error fetch(text) {url_full} -> {err}"))
-            })?;
+    log::info!("The generated files are up to date");
+    Ok(())
+}
+
+/*
+    Prints a unified diff between dir_target and the rendered files, nothing is written or deleted.
+*/
+pub async fn run_dry_run(dir_spec: String, dir_target: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let prefixes = all_prefixes(&dir_spec)?;
+    let prepared = prepare_specs(&dir_spec, &options, registry, wanted_spec).await?;
+
+    let manifest = Manifest::read(&dir_target).await?;
+    let mut count = 0;
 
-            (text, fix_url_param)
+    for PreparedSpec { prefix, spec, methods, generator } in prepared {
+        let files = render_prefix(&dir_spec, &prefix, &spec, &methods, generator.as_ref())?;
+
+        for change in plan_changes(&dir_target, &prefix, &prefixes, &files, &manifest).await? {
+            print!("{}", unified_diff(&change));
+            count += 1;
         }
-    };
+    }
 
-    let spec = parse_spec(spec_source)?;
-    Ok((spec, fix_url_param))
+    log::info!("dry-run: files to change: {count}");
+    Ok(())
 }
 
-async fn remove_files_from_prefix(dir_target: &String, prefix: &String) -> Result<(), ErrorProcess> {
-    let start_with = format!("openapi_{prefix}");
-    let mut read_dir = tokio::fs::read_dir(dir_target).await?;
+pub async fn run_validate(dir_spec: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let prepared = prepare_specs(&dir_spec, &options, registry, wanted_spec).await?;
 
-    while let Ok(Some(entry)) = read_dir.next_entry().await {
-        let path = entry.path();
+    let count = prepared.len();
+    log::info!("The configuration is correct, checked prefixes: {count}");
 
-        let file_name = get_file_name(&path)?;
+    Ok(())
+}
 
-        if file_name.starts_with(&start_with) {
-            if path.is_file() {
-                log::info!("Deleting the old file {path:#?}");
-                tokio::fs::remove_file(path).await?;
-            } else {
-                return Err(ErrorProcess::message(format!("The file was expected -> {file_name}")));
-            }
+/*
+    Prints the parsed specification of the selected methods as json, after applying the rewrite rules.
+    {
+        "prefix": {
+            "methodName": { "url": ..., "method": ..., "handler": ... }
         }
     }
+*/
+pub async fn run_dump_ir(dir_spec: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let prepared = prepare_specs(&dir_spec, &options, registry, wanted_spec).await?;
 
-    Ok(())
-}
+    let mut out = serde_json::Map::new();
 
-async fn run_gen_for_prefix(dir_target: &String, prefix: String, spec: SpecOpenApi, methods: HashMap<String, WantedMethod>) -> Result<(), ErrorProcess> {
-
-    for (method_name, WantedMethod { url, method }) in methods {
-        let name_in_file = format!("openapi_{prefix}_{method_name}");
-        let target_path = format!("{dir_target}/{name_in_file}.ts");
-
-        let sub_sepc = match spec.paths.get(&url) {
-            Some(sub_sepc ) => sub_sepc,
-            None => {
-                let url = &url;
-                return Err(ErrorProcess::message(format!("No path in the specification {url}")));
-            }
-        };
-
-        let handler = match sub_sepc.get(&method) {
-            Some(handler) => handler,
-            None => {
-                let url = &url;
-                let method = &method;
-                return Err(ErrorProcess::message(format!("No method in the specification {url} {method:?}")));
-            }
-        };
-
-        let content_js = generate_js(name_in_file, url, method, handler)?;
-        log::info!("generate_js writh to: {target_path}");
-        tokio::fs::write(target_path, content_js).await?;
+    for PreparedSpec { prefix, spec, methods, .. } in prepared {
+        let mut prefix_out = serde_json::Map::new();
+
+        let mut methods = methods.into_iter().collect::<Vec<_>>();
+        methods.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+
+        for (method_name, operation) in methods {
+            let handler = spec.get_handler(&operation.url, &operation.method)?;
+
+            prefix_out.insert(method_name, serde_json::json!({
+                "url": operation.url,
+                "method": operation.method,
+                "handler": handler,
+            }));
+        }
+
+        out.insert(prefix, serde_json::Value::Object(prefix_out));
     }
 
+    let content = serde_json::to_string_pretty(&out)?;
+    println!("{content}");
+
     Ok(())
 }
+
+fn render_prefix(dir_spec: &String, prefix: &String, spec: &SpecOpenApi, methods: &HashMap<String, OperationRef>, generator: &dyn Generator) -> Result<Vec<RenderedFile>, ErrorProcess> {
+    let input = GeneratorInput { dir_spec, prefix, spec, methods };
+    let mut out = generator.generate(&input)?;
+
+    out.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(out)
+}
@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde_json::Value;
+
+use crate::fetch_spec::{FetchOptions, fetch_spec};
+use crate::open_api_spec::{OperationRef, SpecOpenApi};
+use crate::read_wanted_spec::{WantedMethod, WantedSelection, WantedSource, WantedSpec, parse_wanted_spec};
+use crate::select_methods::{name_from_path, select_methods, suggest_method_name};
+use crate::utils::ErrorProcess;
+
+fn unique_method_name(names: &HashSet<String>, candidates: Vec<String>) -> String {
+    for candidate in candidates.iter() {
+        if !names.contains(candidate) {
+            return candidate.clone();
+        }
+    }
+
+    let base = candidates.first().cloned().unwrap_or_default();
+    let mut index = 2;
+
+    loop {
+        let candidate = format!("{base}{index}");
+
+        if !names.contains(&candidate) {
+            return candidate;
+        }
+
+        index += 1;
+    }
+}
+
+#[test]
+fn test_unique_method_name() {
+    let names: HashSet<String> = ["getDraw".to_string(), "getLotteryDraw".to_string()].into_iter().collect();
+
+    assert_eq!(unique_method_name(&names, vec!("getDraw".into(), "getLotteryDraw".into())), "getDraw2");
+    assert_eq!(unique_method_name(&names, vec!("getDraw".into(), "getLotteryDraws".into())), "getLotteryDraws");
+}
+
+/*
+    Operations that are not yet covered by the wanted spec, sorted by path and method.
+    The names are suggested from the operationId or from the method and the path.
+*/
+fn scaffold_methods(spec: &SpecOpenApi, names: &mut HashSet<String>, covered: &[OperationRef]) -> Vec<(String, OperationRef)> {
+    let mut paths = spec.paths.iter().collect::<Vec<_>>();
+    paths.sort_by_key(|(url, _)| *url);
+
+    let mut out = Vec::new();
+
+    for (url, handlers) in paths {
+        let mut handlers = handlers.iter().collect::<Vec<_>>();
+        handlers.sort_by_key(|(method, _)| method.to_string());
+
+        for (method, handler) in handlers {
+            let operation = OperationRef {
+                url: url.clone(),
+                method: method.clone(),
+            };
+
+            if covered.contains(&operation) {
+                continue;
+            }
+
+            let method_name = unique_method_name(names, vec!(
+                suggest_method_name(url, method, handler),
+                name_from_path(url, method),
+            ));
+
+            names.insert(method_name.clone());
+            out.push((method_name, operation));
+        }
+    }
+
+    out
+}
+
+/*
+    The new operations are appended to "methods", the rest of the file keeps its content and its key order
+*/
+fn add_methods(target_file: &String, wanted_value: &mut Value, new_methods: &[(String, OperationRef)]) -> Result<(), ErrorProcess> {
+    let methods = match wanted_value.as_object_mut() {
+        Some(wanted_object) => wanted_object.entry("methods").or_insert_with(|| Value::Object(Default::default())),
+        None => {
+            return Err(ErrorProcess::message(format!("{target_file}: an object was expected")));
+        }
+    };
+
+    let methods = match methods.as_object_mut() {
+        Some(methods) => methods,
+        None => {
+            return Err(ErrorProcess::message(format!("{target_file}: 'methods' should be an object")));
+        }
+    };
+
+    for (method_name, OperationRef { url, method }) in new_methods.iter().cloned() {
+        methods.insert(method_name, serde_json::to_value(WantedMethod::Path { method, url })?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_add_methods_keeps_order() {
+    use crate::open_api_spec::OpenApiMethod;
+
+    let content = r#"{
+  "source": {"type": "file", "file": "api.json"},
+  "methods": {"order": {"operationId": "postOrder"}, "draw": {"operationId": "getDraw"}},
+  "exclude": [{"methods": ["put"]}],
+  "select": [{"path": "/lottery/**"}]
+}"#;
+
+    let mut wanted_value = serde_json::from_str::<Value>(content).unwrap();
+    let new_methods = vec!((
+        "getInfo".to_string(),
+        OperationRef { url: "/info".into(), method: OpenApiMethod::Get },
+    ));
+
+    add_methods(&"lottery.spec.json".to_string(), &mut wanted_value, &new_methods).unwrap();
+
+    let content = serde_json::to_string(&wanted_value).unwrap();
+    let keys = ["\"source\"", "\"methods\"", "\"order\"", "\"draw\"", "\"getInfo\"", "\"exclude\"", "\"select\""]
+        .map(|key| content.find(key).unwrap());
+
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "{content}");
+}
+
+pub async fn run_scaffold(dir_spec: String, options: FetchOptions, prefix: String, source: Option<WantedSource>) -> Result<(), ErrorProcess> {
+    let target_file = format!("{dir_spec}{prefix}.spec.json");
+    let merge = source.is_none();
+
+    let (mut wanted_value, wanted_spec) = match source {
+        Some(source) => {
+            if Path::new(&target_file).exists() {
+                return Err(ErrorProcess::message(format!("The file {target_file} already exists, use the merge mode")));
+            }
+
+            let wanted_spec = WantedSpec {
+                source,
+                generator: None,
+                selection: WantedSelection::default(),
+            };
+
+            (serde_json::to_value(&wanted_spec)?, wanted_spec)
+        },
+        None => {
+            let content = tokio::fs::read_to_string(&target_file).await.map_err(|err| {
+                ErrorProcess::message(format!("error read content {target_file} -> {err}"))
+            })?;
+
+            let wanted_value = serde_json::from_str::<Value>(&content)?;
+            (wanted_value, parse_wanted_spec(content)?)
+        }
+    };
+
+    let WantedSpec { source, selection, .. } = wanted_spec;
+    let spec = fetch_spec(&dir_spec, &options, &source).await?;
+
+    let covered = select_methods(&spec, selection)?;
+    let mut names = covered.keys().cloned().collect::<HashSet<String>>();
+    let covered = covered.into_values().collect::<Vec<OperationRef>>();
+
+    let new_methods = scaffold_methods(&spec, &mut names, &covered);
+
+    add_methods(&target_file, &mut wanted_value, &new_methods)?;
+
+    for (method_name, OperationRef { url, method }) in new_methods.iter() {
+        log::info!("scaffold {prefix}: {method_name} -> {method} {url}");
+    }
+
+    if merge && new_methods.is_empty() {
+        log::info!("scaffold {prefix}: no new operations in {target_file}");
+        return Ok(());
+    }
+
+    let content = serde_json::to_string_pretty(&wanted_value)?;
+    tokio::fs::write(&target_file, format!("{content}\n")).await?;
+
+    let count = new_methods.len();
+    log::info!("scaffold {prefix}: {count} operations written to {target_file}");
+
+    Ok(())
+}
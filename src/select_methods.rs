@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use crate::open_api_spec::{OpenApiMethod, OperationRef, SpecHandlerType, SpecOpenApi};
+use crate::read_wanted_spec::{WantedMethod, WantedSelection, WantedSelector};
+use crate::generate_js::word_first_letter_to_upper;
+use crate::utils::ErrorProcess;
+use crate::validate::did_you_mean;
+
+/*
+    convert operationId to the method name:
+    get-current_draw.v2
+    getCurrentDrawV2
+*/
+pub fn name_from_operation_id(operation_id: &str) -> String {
+    operation_id
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|chunk| !chunk.is_empty())
+        .enumerate()
+        .map(|(key, chunk)| {
+            if key == 0 {
+                chunk.to_string()
+            } else {
+                word_first_letter_to_upper(chunk)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_name_from_operation_id() {
+    assert_eq!(name_from_operation_id("getCurrentDraw"), "getCurrentDraw");
+    assert_eq!(name_from_operation_id("get-current_draw.v2"), "getCurrentDrawV2");
+    assert_eq!(name_from_operation_id("put_info"), "putInfo");
+}
+
+/*
+    convert method + path to the method name:
+    get /lottery/draws/current/{universe}
+    getLotteryDrawsCurrentByUniverse
+*/
+pub fn name_from_path(url: &str, method: &OpenApiMethod) -> String {
+    let mut out = vec!(method.to_string());
+
+    for chunk in url.split('/').filter(|chunk| !chunk.is_empty()) {
+        let chunk_name = word_first_letter_to_upper(&name_from_operation_id(chunk));
+
+        if chunk.starts_with('{') && chunk.ends_with('}') {
+            out.push(format!("By{chunk_name}"));
+        } else {
+            out.push(chunk_name);
+        }
+    }
+
+    out.join("")
+}
+
+#[test]
+fn test_name_from_path() {
+    assert_eq!(name_from_path("/lottery/draws/current/{universe}", &OpenApiMethod::Get), "getLotteryDrawsCurrentByUniverse");
+    assert_eq!(name_from_path("/lottery/orders/{account-id}", &OpenApiMethod::Post), "postLotteryOrdersByAccountId");
+}
+
+pub fn suggest_method_name(url: &str, method: &OpenApiMethod, handler: &SpecHandlerType) -> String {
+    match &handler.operation_id {
+        Some(operation_id) => name_from_operation_id(operation_id),
+        None => name_from_path(url, method),
+    }
+}
+
+fn glob_match_segment(pattern: &[char], segment: &[char]) -> bool {
+    match pattern.split_first() {
+        None => segment.is_empty(),
+        Some(('*', rest)) => {
+            (0..=segment.len()).any(|skip| glob_match_segment(rest, &segment[skip..]))
+        },
+        Some(('?', rest)) => {
+            !segment.is_empty() && glob_match_segment(rest, &segment[1..])
+        },
+        Some((char, rest)) => {
+            segment.first() == Some(char) && glob_match_segment(rest, &segment[1..])
+        },
+    }
+}
+
+fn glob_match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skip| glob_match_segments(rest, &path[skip..]))
+        },
+        Some((pattern_segment, rest)) => {
+            match path.split_first() {
+                Some((path_segment, path_rest)) => {
+                    let pattern_chars = pattern_segment.chars().collect::<Vec<char>>();
+                    let path_chars = path_segment.chars().collect::<Vec<char>>();
+
+                    glob_match_segment(&pattern_chars, &path_chars) && glob_match_segments(rest, path_rest)
+                },
+                None => false,
+            }
+        }
+    }
+}
+
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.split('/').collect::<Vec<&str>>();
+    let path = path.split('/').collect::<Vec<&str>>();
+
+    glob_match_segments(&pattern, &path)
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("/lottery/**", "/lottery/draws/current/{universe}"));
+    assert!(glob_match("/lottery/**", "/lottery"));
+    assert!(!glob_match("/lottery/**", "/lottery2/draws"));
+    assert!(glob_match("/lottery/*/current/*", "/lottery/draws/current/{universe}"));
+    assert!(!glob_match("/lottery/*", "/lottery/draws/current"));
+    assert!(glob_match("/lottery/draw?", "/lottery/draws"));
+    assert!(glob_match("/**/{accountId}", "/lottery/orders/{accountId}"));
+}
+
+fn selector_match(selector: &WantedSelector, url: &str, method: &OpenApiMethod, handler: &SpecHandlerType) -> bool {
+    if !selector.tags.is_empty() && !selector.tags.iter().any(|tag| handler.tags.contains(tag)) {
+        return false;
+    }
+
+    if let Some(path) = &selector.path {
+        if !glob_match(path, url) {
+            return false;
+        }
+    }
+
+    if !selector.methods.is_empty() && !selector.methods.contains(method) {
+        return false;
+    }
+
+    true
+}
+
+pub fn select_methods(spec: &SpecOpenApi, selection: WantedSelection) -> Result<HashMap<String, OperationRef>, ErrorProcess> {
+    let mut problems = Vec::new();
+    let out = collect_methods(spec, selection, &mut problems);
+
+    if problems.is_empty() {
+        Ok(out)
+    } else {
+        Err(ErrorProcess::message(problems.join("\n")))
+    }
+}
+
+fn find_operation(spec: &SpecOpenApi, operation_id: &String) -> Result<OperationRef, String> {
+    if let Some(list) = spec.ambiguous_operations.get(operation_id) {
+        let mut list = list.iter().map(|OperationRef { url, method }| format!("{method} {url}")).collect::<Vec<String>>();
+        list.sort();
+        let list = list.join(", ");
+
+        return Err(format!("Duplicate operationId {operation_id} in the specification: {list}"));
+    }
+
+    match spec.operations.get(operation_id) {
+        Some(operation) => Ok(operation.clone()),
+        None => {
+            let suggestion = did_you_mean(operation_id, spec.operations.keys());
+            Err(format!("No operationId in the specification {operation_id}{suggestion}"))
+        }
+    }
+}
+
+/*
+    The same as select_methods, but every problem is added to the list instead of stopping at the first one.
+*/
+pub fn collect_methods(spec: &SpecOpenApi, selection: WantedSelection, problems: &mut Vec<String>) -> HashMap<String, OperationRef> {
+    let WantedSelection { methods, operations, select, exclude } = selection;
+
+    let mut out = HashMap::new();
+
+    for (method_name, wanted_method) in methods {
+        let operation = match wanted_method {
+            WantedMethod::Path { url, method } => OperationRef { url, method },
+            WantedMethod::OperationId { operation_id } => match find_operation(spec, &operation_id) {
+                Ok(operation) => operation,
+                Err(problem) => {
+                    problems.push(format!("{method_name}: {problem}"));
+                    continue;
+                }
+            },
+        };
+
+        out.insert(method_name, operation);
+    }
+
+    for operation_id in operations {
+        let operation = match find_operation(spec, &operation_id) {
+            Ok(operation) => operation,
+            Err(problem) => {
+                problems.push(problem);
+                continue;
+            }
+        };
+
+        let method_name = name_from_operation_id(&operation_id);
+
+        if out.insert(method_name.clone(), operation).is_some() {
+            problems.push(format!("Duplicate method name {method_name} (operationId {operation_id})"));
+        }
+    }
+
+    if select.is_empty() {
+        return out;
+    }
+
+    for (url, handlers) in spec.paths.iter() {
+        for (method, handler) in handlers.iter() {
+            let is_selected = select.iter().any(|selector| selector_match(selector, url, method, handler));
+            let is_excluded = exclude.iter().any(|selector| selector_match(selector, url, method, handler));
+
+            if !is_selected || is_excluded {
+                continue;
+            }
+
+            let operation = OperationRef {
+                url: url.clone(),
+                method: method.clone(),
+            };
+
+            //explicit entries take precedence over the selectors
+            if out.values().any(|item| *item == operation) {
+                continue;
+            }
+
+            let method_name = suggest_method_name(url, method, handler);
+
+            if out.insert(method_name.clone(), operation).is_some() {
+                problems.push(format!("Duplicate method name {method_name} (selected {method} {url})"));
+            }
+        }
+    }
+
+    out
+}
+
+#[test]
+fn test_collect_methods_ambiguous_operation_id() {
+    let spec = crate::parse_spec::parse_spec(serde_json::json!({
+        "paths": {
+            "/lottery/draws": { "get": { "operationId": "list" } },
+            "/lottery/orders": { "get": { "operationId": "list" }, "post": { "operationId": "postOrder" } }
+        }
+    }).to_string()).unwrap();
+
+    let selection = serde_json::from_value::<WantedSelection>(serde_json::json!({ "operations": ["postOrder"] })).unwrap();
+    let mut problems = Vec::new();
+    let methods = collect_methods(&spec, selection, &mut problems);
+    assert_eq!(problems, Vec::<String>::new());
+    assert_eq!(methods.len(), 1);
+
+    let selection = serde_json::from_value::<WantedSelection>(serde_json::json!({ "methods": { "list": { "operationId": "list" } } })).unwrap();
+    let mut problems = Vec::new();
+    collect_methods(&spec, selection, &mut problems);
+    assert_eq!(problems, vec!("list: Duplicate operationId list in the specification: get /lottery/draws, get /lottery/orders".to_string()));
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::content_hash;
+use crate::utils::{ErrorProcess, get_file_name};
+
+pub const LOCK_FILE: &str = "openapi.lock.json";
+pub const DEFAULT_CACHE_DIR: &str = ".openapi_cache";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheMode {
+    Fetch,                      //everything is fetched, only the missing sources are added to the lock, the locked version from the cache is used when the fetch fails
+    Offline,                    //only the locked version from the cache, nothing is fetched
+    Update,                     //everything is fetched and the lock is updated, a failed fetch is an error and the changes are reported
+}
+
+/*
+    {
+        "specs": {
+            "http://api/lottery-integration/meta/open-api": "sha256 of the content",
+            "http://api/partner/meta/open-api {\"headers\":{\"X-Partner\":\"${PARTNER}\"}}": "sha256 of the content"
+        }
+    }
+*/
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpecLock {
+    pub specs: BTreeMap<String, String>,
+}
+
+impl SpecLock {
+    pub async fn read(dir_spec: &String) -> Result<SpecLock, ErrorProcess> {
+        let path = format!("{dir_spec}{LOCK_FILE}");
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => {
+                let lock = serde_json::from_str::<SpecLock>(&content).map_err(|err| {
+                    ErrorProcess::message(format!("error decode lock {path} -> {err}"))
+                })?;
+
+                Ok(lock)
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SpecLock::default()),
+            Err(err) => Err(ErrorProcess::message(format!("error read content {path} -> {err}"))),
+        }
+    }
+
+    pub async fn write(&self, dir_spec: &String) -> Result<(), ErrorProcess> {
+        let path = format!("{dir_spec}{LOCK_FILE}");
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, format!("{content}\n")).await?;
+        Ok(())
+    }
+}
+
+/*
+    The cached files are named after the hash of their content, so every locked version can be kept.
+    A file whose content does not match its name is treated as missing.
+*/
+pub async fn read_cache(cache_dir: &String, hash: &String) -> Result<Option<String>, ErrorProcess> {
+    let path = format!("{cache_dir}/{hash}.json");
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => {
+            if content_hash(&content) != *hash {
+                log::warn!("The cached file {path} is damaged, ignoring it");
+                return Ok(None);
+            }
+
+            Ok(Some(content))
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(ErrorProcess::message(format!("error read content {path} -> {err}"))),
+    }
+}
+
+pub async fn write_cache(cache_dir: &String, hash: &String, content: &String) -> Result<(), ErrorProcess> {
+    tokio::fs::create_dir_all(cache_dir).await?;
+
+    let path = format!("{cache_dir}/{hash}.json");
+    tokio::fs::write(path, content).await?;
+    Ok(())
+}
+
+/*
+    Removes the cached files which are not referenced by the lock, called after the lock is written.
+    {cache_dir}/{hash}.json -> removed when no source is locked to {hash}
+*/
+pub async fn prune_cache(cache_dir: &String, lock: &SpecLock) -> Result<(), ErrorProcess> {
+    let mut entries = match tokio::fs::read_dir(cache_dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(ErrorProcess::message(format!("error read dir {cache_dir} -> {err}"))),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let file_name = get_file_name(&path)?;
+
+        let hash = match file_name.strip_suffix(".json") {
+            Some(hash) => hash,
+            None => continue,
+        };
+
+        if !lock.specs.values().any(|locked| locked == hash) {
+            log::info!("remove unused cached spec {}", path.display());
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use core::hash::Hash;
 use std::fmt::Debug;
+use serde::{Serialize, Serializer, ser::SerializeMap};
 
 pub fn get_file_name(path: &PathBuf) -> Result<String, ErrorProcess> {
     let file_name = match path.file_name() {
@@ -57,6 +58,17 @@ impl ErrorProcess {
     }
 }
 
+impl std::fmt::Display for ErrorProcess {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Message(message) => write!(fmt, "{message}"),
+            Self::DeserializeError(err) => write!(fmt, "{err}"),
+            Self::ParseError(err) => write!(fmt, "{err}"),
+            Self::StdError(err) => write!(fmt, "{err}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 
 pub struct OrderHashMap<K, V> {
@@ -99,4 +111,17 @@ impl<K: Eq + Hash + Clone + Debug + Ord, V> OrderHashMap<K, V> {
 
         result
     }
-}
\ No newline at end of file
+}
+
+impl<K: Eq + Hash + Clone + Debug + Ord + Serialize, V: Serialize> Serialize for OrderHashMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let sorted = self.get_sorted();
+        let mut map = serializer.serialize_map(Some(sorted.len()))?;
+
+        for (key, value) in sorted {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
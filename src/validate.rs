@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::fetch_spec::{FetchOptions, fetch_specs, source_key};
+use crate::generate_js::to_big_camel_case;
+use crate::generator::{Generator, GeneratorRegistry, get_generator};
+use crate::open_api_spec::{OperationRef, SpecOpenApi};
+use crate::parse_spec::rewrite_url;
+use crate::read_wanted_spec::WantedSpec;
+use crate::select_methods::collect_methods;
+use crate::utils::ErrorProcess;
+
+pub struct PreparedSpec {
+    pub prefix: String,
+    pub spec: SpecOpenApi,
+    pub methods: HashMap<String, OperationRef>,
+    pub generator: Box<dyn Generator>,
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b_chars.len()).collect::<Vec<usize>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec!(i + 1);
+
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            let value = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+            current.push(value);
+        }
+
+        prev = current;
+    }
+
+    prev[b_chars.len()]
+}
+
+#[test]
+fn test_levenshtein() {
+    assert_eq!(levenshtein("", "abc"), 3);
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+    assert_eq!(levenshtein("/lottery/draws", "/lottery/draws"), 0);
+}
+
+/*
+    The names of the path parameters do not matter when comparing paths:
+    /lottery/{universe}/draws
+    /lottery/{}/draws
+*/
+fn normalize_path(path: &str) -> String {
+    path.split('/')
+        .map(|chunk| {
+            if chunk.starts_with('{') && chunk.ends_with('}') {
+                "{}"
+            } else {
+                chunk
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/*
+    ", did you mean: /lottery/draws/{universe}, /lottery/draw/{universe}?" or an empty string
+*/
+pub fn did_you_mean<'a>(value: &str, candidates: impl Iterator<Item = &'a String>) -> String {
+    let value_normalized = normalize_path(value);
+    let max_distance = (value.chars().count() / 3).max(2);
+
+    let mut similar = candidates
+        .map(|candidate| (levenshtein(&value_normalized, &normalize_path(candidate)), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<(usize, &String)>>();
+
+    similar.sort();
+
+    let similar = similar
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.as_str())
+        .collect::<Vec<&str>>();
+
+    if similar.is_empty() {
+        return String::new();
+    }
+
+    let similar = similar.join(", ");
+    format!(", did you mean: {similar}?")
+}
+
+#[test]
+fn test_did_you_mean() {
+    let candidates: Vec<String> = vec!("/lottery/draws/current/{universe}".into(), "/lottery/orders".into(), "/casino/games".into());
+
+    assert_eq!(
+        did_you_mean("/lottery/draw/current/{universeId}", candidates.iter()),
+        ", did you mean: /lottery/draws/current/{universe}?"
+    );
+    assert_eq!(did_you_mean("/sport/events", candidates.iter()), "");
+}
+
+fn validate_methods(spec: &SpecOpenApi, methods: &HashMap<String, OperationRef>, problems: &mut Vec<String>) {
+    let mut methods = methods.iter().collect::<Vec<_>>();
+    methods.sort_by_key(|(method_name, _)| *method_name);
+
+    for (method_name, OperationRef { url, method }) in methods {
+        match spec.paths.get(url) {
+            Some(handlers) => match handlers.get(method) {
+                Some(handler) => {
+                    if let Some(security_error) = &handler.security_error {
+                        problems.push(format!("{method_name}: {url} {method}: {security_error}"));
+                    }
+                },
+                None => {
+                    let mut available = handlers.keys().map(|method| method.to_string()).collect::<Vec<String>>();
+                    available.sort();
+                    let available = available.join(", ");
+
+                    problems.push(format!("{method_name}: No method in the specification {url} {method}, available: {available}"));
+                }
+            },
+            None => {
+                let suggestion = did_you_mean(url, spec.paths.keys());
+                problems.push(format!("{method_name}: No path in the specification {url}{suggestion}"));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_validate_methods_security_error() {
+    let spec = crate::parse_spec::parse_spec(serde_json::json!({
+        "paths": {
+            "/lottery/orders": {
+                "get": { "operationId": "getOrders", "security": [{ "partnerCert": [] }] },
+                "post": { "operationId": "postOrder" }
+            }
+        },
+        "components": {
+            "securitySchemes": {
+                "partnerCert": { "type": "mutualTLS" }
+            }
+        }
+    }).to_string()).unwrap();
+
+    let mut problems = Vec::new();
+    let methods = HashMap::from([("postOrder".to_string(), spec.operations["postOrder"].clone())]);
+    validate_methods(&spec, &methods, &mut problems);
+    assert_eq!(problems, Vec::<String>::new());
+
+    let methods = HashMap::from([("getOrders".to_string(), spec.operations["getOrders"].clone())]);
+    validate_methods(&spec, &methods, &mut problems);
+    assert_eq!(problems, vec!("getOrders: /lottery/orders get: Security scheme partnerCert: unsupported type = mutualTLS".to_string()));
+}
+
+/*
+    The generated types use the name converted by to_big_camel_case, two different names can give the same type:
+    openapi_lottery_get_draw -> OpenapiLotteryGetDraw
+    openapi_lottery_getDraw -> OpenapiLotteryGetDraw
+*/
+fn validate_names(prepared: &[PreparedSpec], problems: &mut Vec<String>) {
+    let mut names: HashMap<String, Vec<String>> = HashMap::new();
+
+    for PreparedSpec { prefix, methods, .. } in prepared {
+        for method_name in methods.keys() {
+            let name_in_file = format!("openapi_{prefix}_{method_name}");
+            names.entry(to_big_camel_case(&name_in_file)).or_default().push(name_in_file);
+        }
+    }
+
+    let mut names = names.into_iter().filter(|(_, list)| list.len() > 1).collect::<Vec<_>>();
+    names.sort();
+
+    for (camel_case, mut list) in names {
+        list.sort();
+        let list = list.join(", ");
+        problems.push(format!("Names collide after conversion to {camel_case}: {list}"));
+    }
+}
+
+/*
+    None -> the generator could not be created, the problem is added to the list
+*/
+fn prepare_spec(prefix: &String, mut spec: SpecOpenApi, wanted_spec: WantedSpec, registry: &GeneratorRegistry, problems: &mut Vec<String>) -> Option<PreparedSpec> {
+    let WantedSpec { source, generator, selection } = wanted_spec;
+
+    let mut prefix_problems = Vec::new();
+    let mut methods = collect_methods(&spec, selection, &mut prefix_problems);
+
+    for rule in source.get_rewrite_rules().iter() {
+        if let Err(err) = rewrite_url(&mut spec, &mut methods, rule) {
+            prefix_problems.push(err.to_string());
+        }
+    }
+
+    validate_methods(&spec, &methods, &mut prefix_problems);
+
+    let generator = match get_generator(&generator.unwrap_or_default(), registry) {
+        Ok(generator) => Some(generator),
+        Err(err) => {
+            prefix_problems.push(err.to_string());
+            None
+        }
+    };
+
+    for problem in prefix_problems {
+        problems.push(format!("{prefix}: {problem}"));
+    }
+
+    Some(PreparedSpec {
+        prefix: prefix.clone(),
+        spec,
+        methods,
+        generator: generator?,
+    })
+}
+
+/*
+    Loads every source and checks the whole configuration, all problems are reported at once.
+    The sources are fetched concurrently, a source shared by several prefixes is fetched once.
+    Nothing is written to dir_target.
+*/
+pub async fn prepare_specs(dir_spec: &String, options: &FetchOptions, registry: &GeneratorRegistry, wanted_spec: HashMap<String, WantedSpec>) -> Result<Vec<PreparedSpec>, ErrorProcess> {
+    let mut wanted_spec = wanted_spec.into_iter().collect::<Vec<_>>();
+    wanted_spec.sort_by(|(prefix1, _), (prefix2, _)| prefix1.cmp(prefix2));
+
+    let sources = wanted_spec.iter().map(|(_, wanted_spec)| wanted_spec.source.clone()).collect();
+    let specs = fetch_specs(dir_spec, options, sources).await?;
+
+    let mut problems = Vec::new();
+    let mut prepared = Vec::new();
+
+    for (prefix, wanted_spec) in wanted_spec {
+        let key = source_key(dir_spec, options, &wanted_spec.source);
+
+        match specs.get(&key) {
+            Some(Ok(spec)) => prepared.extend(prepare_spec(&prefix, spec.clone(), wanted_spec, registry, &mut problems)),
+            Some(Err(err)) => problems.push(format!("{prefix}: {err}")),
+            None => problems.push(format!("{prefix}: the source {key} has not been fetched")),
+        }
+    }
+
+    validate_names(&prepared, &mut problems);
+
+    if !problems.is_empty() {
+        for problem in problems.iter() {
+            eprintln!("{problem}");
+        }
+
+        let count = problems.len();
+        return Err(ErrorProcess::message(format!("Invalid configuration, problems found: {count}")));
+    }
+
+    Ok(prepared)
+}
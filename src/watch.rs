@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+use crate::fetch_spec::FetchOptions;
+use crate::generator::GeneratorRegistry;
+use crate::read_wanted_spec::{GeneratorConfig, TemplateOptions, WantedSource, WantedSpec, parse_spec_name};
+use crate::run_gen::{read_wanted_spec_for, run_gen};
+use crate::utils::ErrorProcess;
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/*
+    Editors often save by renaming a temporary file, so the path may not exist for a moment.
+    In that case only the directory is resolved.
+*/
+fn normalize_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(file_name)) => match parent.canonicalize() {
+            Ok(parent) => parent.join(file_name),
+            Err(_) => path.to_path_buf(),
+        },
+        _ => path.to_path_buf(),
+    }
+}
+
+/*
+    prefix -> the files which the prefix depends on
+    lottery -> [spec/lottery.spec.json, spec/api.json, spec/templates/request.ts.j2]
+*/
+fn get_dependencies(dir_spec: &String, wanted_spec: &HashMap<String, WantedSpec>) -> HashMap<String, Vec<PathBuf>> {
+    let mut out = HashMap::new();
+
+    for (prefix, WantedSpec { source, generator, .. }) in wanted_spec {
+        let mut files = vec!(normalize_path(Path::new(&format!("{dir_spec}{prefix}.spec.json"))));
+
+        if let WantedSource::File { file, .. } = source {
+            files.push(normalize_path(Path::new(&format!("{dir_spec}{file}"))));
+        }
+
+        if let Some(GeneratorConfig::Template(TemplateOptions { template, .. })) = generator {
+            files.push(normalize_path(Path::new(&format!("{dir_spec}{template}"))));
+        }
+
+        out.insert(prefix.clone(), files);
+    }
+
+    out
+}
+
+/*
+    A new or deleted *.spec.json file in dir_spec is not in the dependencies yet (or any more),
+    its prefix is taken from the file name.
+*/
+fn get_affected(dir_spec: &Path, dependencies: &HashMap<String, Vec<PathBuf>>, changed: &HashSet<PathBuf>) -> Vec<String> {
+    let mut out = HashSet::new();
+
+    for path in changed {
+        if path.parent() == Some(dir_spec) {
+            if let Ok(Some(prefix)) = parse_spec_name(path) {
+                out.insert(prefix);
+            }
+        }
+
+        for (prefix, files) in dependencies {
+            if files.contains(path) {
+                out.insert(prefix.clone());
+            }
+        }
+    }
+
+    let mut out = out.into_iter().collect::<Vec<String>>();
+    out.sort();
+    out
+}
+
+/*
+    The spec files referenced from outside dir_spec are watched through their directories.
+*/
+fn update_watched(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut HashSet<PathBuf>,
+    dir_spec: &Path,
+    dependencies: &HashMap<String, Vec<PathBuf>>,
+) {
+    for files in dependencies.values() {
+        for file in files {
+            let dir = match file.parent() {
+                Some(dir) => dir.to_path_buf(),
+                None => continue,
+            };
+
+            if dir.starts_with(dir_spec) || watched.contains(&dir) {
+                continue;
+            }
+
+            match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    log::info!("watch: {dir:?}");
+                    watched.insert(dir);
+                },
+                Err(err) => log::error!("watch: cannot watch {dir:?} -> {err}"),
+            }
+        }
+    }
+}
+
+/*
+    Waits for the first change and then collects the following ones until it is quiet for DEBOUNCE.
+    None -> the watcher has stopped.
+*/
+async fn next_changes(receiver: &mut UnboundedReceiver<notify::Result<Event>>) -> Option<HashSet<PathBuf>> {
+    let mut changed = HashSet::new();
+
+    loop {
+        let event = if changed.is_empty() {
+            receiver.recv().await?
+        } else {
+            match tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+                Ok(Some(event)) => event,
+                Ok(None) | Err(_) => return Some(changed),
+            }
+        };
+
+        match event {
+            Ok(Event { kind: EventKind::Access(_), .. }) => {},
+            Ok(Event { paths, .. }) => {
+                changed.extend(paths.iter().map(|path| normalize_path(path)));
+            },
+            Err(err) => log::error!("watch: {err}"),
+        }
+    }
+}
+
+async fn generate(dir_spec: &str, dir_target: &str, options: &FetchOptions, registry: &GeneratorRegistry, only: Vec<String>, force: bool) {
+    let list = only.join(", ");
+    eprintln!("watch: generating {list}");
+
+    match run_gen(dir_spec.to_string(), dir_target.to_string(), options.clone(), registry, only, force).await {
+        Ok(()) => eprintln!("watch: done"),
+        Err(err) => log::error!("watch: {err}"),
+    }
+}
+
+/*
+    Generates the selected prefixes and then regenerates them after every change of the configuration
+    or of the local spec files. Errors are reported and the watching continues.
+*/
+pub async fn run_watch(dir_spec: String, dir_target: String, options: FetchOptions, registry: &GeneratorRegistry, only: Vec<String>, force: bool) -> Result<(), ErrorProcess> {
+    let wanted_spec = read_wanted_spec_for(&dir_spec, &only)?;
+    let mut dependencies = get_dependencies(&dir_spec, &wanted_spec);
+
+    let dir_spec_full = Path::new(&dir_spec).canonicalize()?;
+
+    let (sender, mut receiver) = unbounded_channel();
+
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+    }).map_err(|err| {
+        ErrorProcess::message(format!("watch: cannot start the watcher -> {err}"))
+    })?;
+
+    watcher.watch(&dir_spec_full, RecursiveMode::Recursive).map_err(|err| {
+        ErrorProcess::message(format!("watch: cannot watch {dir_spec} -> {err}"))
+    })?;
+
+    let mut watched = HashSet::new();
+    update_watched(&mut watcher, &mut watched, &dir_spec_full, &dependencies);
+
+    let mut all = dependencies.keys().cloned().collect::<Vec<String>>();
+    all.sort();
+    generate(&dir_spec, &dir_target, &options, registry, all, force).await;
+
+    while let Some(changed) = next_changes(&mut receiver).await {
+        let affected = get_affected(&dir_spec_full, &dependencies, &changed);
+
+        if affected.is_empty() {
+            continue;
+        }
+
+        let wanted_spec = match read_wanted_spec_for(&dir_spec, &only) {
+            Ok(wanted_spec) => wanted_spec,
+            Err(err) => {
+                log::error!("watch: {err}");
+                continue;
+            }
+        };
+
+        dependencies = get_dependencies(&dir_spec, &wanted_spec);
+        update_watched(&mut watcher, &mut watched, &dir_spec_full, &dependencies);
+
+        let (affected, removed): (Vec<String>, Vec<String>) = affected
+            .into_iter()
+            .partition(|prefix| wanted_spec.contains_key(prefix));
+
+        for prefix in removed {
+            if only.is_empty() || only.contains(&prefix) {
+                eprintln!("watch: {prefix}.spec.json was removed, the generated files are kept");
+            }
+        }
+
+        if !affected.is_empty() {
+            generate(&dir_spec, &dir_target, &options, registry, affected, force).await;
+        }
+    }
+
+    Ok(())
+}